@@ -0,0 +1,250 @@
+use anyhow_ext::anyhow;
+use anyhow_ext::Error as AnyhowError;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcProgramAccountsConfig, RpcSendTransactionConfig};
+use solana_client::rpc_response::RpcPrioritizationFee;
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::VersionedTransaction;
+use std::future::Future;
+use std::pin::Pin;
+
+pub type FetchFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, AnyhowError>> + Send + 'a>>;
+
+/// The RPC calls the crate makes. Implemented for `RpcClient`; implement it
+/// over in-memory accounts to exercise the loaders and the update path
+/// without a live RPC. Only the account reads are required: fee estimation,
+/// scanning and sending fail by default, so test doubles can skip them.
+pub trait AccountFetcher: Sync {
+    /// The data of the account at `key`, failing if it does not exist
+    fn get_account_data<'a>(&'a self, key: &'a Pubkey) -> FetchFuture<'a, Vec<u8>>;
+
+    /// The accounts at `keys`, in order, with `None` for missing accounts
+    fn get_multiple_accounts<'a>(
+        &'a self,
+        keys: &'a [Pubkey],
+    ) -> FetchFuture<'a, Vec<Option<Account>>>;
+
+    /// Like `get_multiple_accounts`, read at `commitment`
+    fn get_multiple_accounts_with_commitment<'a>(
+        &'a self,
+        keys: &'a [Pubkey],
+        commitment: CommitmentConfig,
+    ) -> FetchFuture<'a, Vec<Option<Account>>>;
+
+    /// The account at `key` read at `commitment`, or `None` if it does not exist
+    fn get_account_with_commitment<'a>(
+        &'a self,
+        key: &'a Pubkey,
+        commitment: CommitmentConfig,
+    ) -> FetchFuture<'a, Option<Account>>;
+
+    /// Like `get_account_with_commitment`, along with the slot the node read it at
+    fn get_account_with_slot<'a>(
+        &'a self,
+        key: &'a Pubkey,
+        commitment: CommitmentConfig,
+    ) -> FetchFuture<'a, (u64, Option<Account>)>;
+
+    /// The commitment used when none is given
+    fn commitment(&self) -> CommitmentConfig {
+        CommitmentConfig::default()
+    }
+
+    /// The accounts owned by `program_id` matching `config`
+    fn get_program_accounts_with_config<'a>(
+        &'a self,
+        _program_id: &'a Pubkey,
+        _config: RpcProgramAccountsConfig,
+    ) -> FetchFuture<'a, Vec<(Pubkey, Account)>> {
+        unsupported("get_program_accounts_with_config")
+    }
+
+    /// The prioritization fees recently paid by transactions writing to `accounts`
+    fn get_recent_prioritization_fees<'a>(
+        &'a self,
+        _accounts: &'a [Pubkey],
+    ) -> FetchFuture<'a, Vec<RpcPrioritizationFee>> {
+        unsupported("get_recent_prioritization_fees")
+    }
+
+    /// The blockhash new transactions should reference
+    fn get_latest_blockhash(&self) -> FetchFuture<'_, Hash> {
+        unsupported("get_latest_blockhash")
+    }
+
+    /// Sends `transaction`, returning its signature
+    fn send_transaction_with_config<'a>(
+        &'a self,
+        _transaction: &'a VersionedTransaction,
+        _config: RpcSendTransactionConfig,
+    ) -> FetchFuture<'a, Signature> {
+        unsupported("send_transaction_with_config")
+    }
+}
+
+fn unsupported<'a, T: Send + 'a>(method: &'static str) -> FetchFuture<'a, T> {
+    Box::pin(async move { Err(anyhow!("AccountFetcher.{}: not supported", method)) })
+}
+
+impl AccountFetcher for RpcClient {
+    fn get_account_data<'a>(&'a self, key: &'a Pubkey) -> FetchFuture<'a, Vec<u8>> {
+        Box::pin(async move { Ok(RpcClient::get_account_data(self, key).await?) })
+    }
+
+    fn get_multiple_accounts<'a>(
+        &'a self,
+        keys: &'a [Pubkey],
+    ) -> FetchFuture<'a, Vec<Option<Account>>> {
+        Box::pin(async move { Ok(RpcClient::get_multiple_accounts(self, keys).await?) })
+    }
+
+    fn get_multiple_accounts_with_commitment<'a>(
+        &'a self,
+        keys: &'a [Pubkey],
+        commitment: CommitmentConfig,
+    ) -> FetchFuture<'a, Vec<Option<Account>>> {
+        Box::pin(async move {
+            Ok(
+                RpcClient::get_multiple_accounts_with_commitment(self, keys, commitment)
+                    .await?
+                    .value,
+            )
+        })
+    }
+
+    fn get_account_with_commitment<'a>(
+        &'a self,
+        key: &'a Pubkey,
+        commitment: CommitmentConfig,
+    ) -> FetchFuture<'a, Option<Account>> {
+        Box::pin(async move {
+            Ok(
+                RpcClient::get_account_with_commitment(self, key, commitment)
+                    .await?
+                    .value,
+            )
+        })
+    }
+
+    fn get_account_with_slot<'a>(
+        &'a self,
+        key: &'a Pubkey,
+        commitment: CommitmentConfig,
+    ) -> FetchFuture<'a, (u64, Option<Account>)> {
+        Box::pin(async move {
+            let response = RpcClient::get_account_with_commitment(self, key, commitment).await?;
+            Ok((response.context.slot, response.value))
+        })
+    }
+
+    fn commitment(&self) -> CommitmentConfig {
+        RpcClient::commitment(self)
+    }
+
+    fn get_program_accounts_with_config<'a>(
+        &'a self,
+        program_id: &'a Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> FetchFuture<'a, Vec<(Pubkey, Account)>> {
+        Box::pin(async move {
+            Ok(RpcClient::get_program_accounts_with_config(self, program_id, config).await?)
+        })
+    }
+
+    fn get_recent_prioritization_fees<'a>(
+        &'a self,
+        accounts: &'a [Pubkey],
+    ) -> FetchFuture<'a, Vec<RpcPrioritizationFee>> {
+        Box::pin(
+            async move { Ok(RpcClient::get_recent_prioritization_fees(self, accounts).await?) },
+        )
+    }
+
+    fn get_latest_blockhash(&self) -> FetchFuture<'_, Hash> {
+        Box::pin(async move { Ok(RpcClient::get_latest_blockhash(self).await?) })
+    }
+
+    fn send_transaction_with_config<'a>(
+        &'a self,
+        transaction: &'a VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> FetchFuture<'a, Signature> {
+        Box::pin(async move {
+            Ok(RpcClient::send_transaction_with_config(self, transaction, config).await?)
+        })
+    }
+}
+
+/// In-memory accounts for exercising the loaders in unit tests
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct MockFetcher {
+    pub accounts: std::collections::HashMap<Pubkey, Vec<u8>>,
+    /// The number of keys in each `get_multiple_accounts` call, in call order
+    pub batches: std::sync::Mutex<Vec<usize>>,
+    /// The keys of each `get_multiple_accounts*` call, in call order
+    pub requests: std::sync::Mutex<Vec<Vec<Pubkey>>>,
+}
+
+#[cfg(test)]
+impl MockFetcher {
+    fn account(&self, key: &Pubkey) -> Option<Account> {
+        self.accounts.get(key).map(|data| Account {
+            data: data.clone(),
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+impl AccountFetcher for MockFetcher {
+    fn get_account_data<'a>(&'a self, key: &'a Pubkey) -> FetchFuture<'a, Vec<u8>> {
+        Box::pin(async move {
+            self.account(key)
+                .map(|x| x.data)
+                .ok_or_else(|| anyhow_ext::anyhow!("MockFetcher: account {} not found", key))
+        })
+    }
+
+    fn get_multiple_accounts<'a>(
+        &'a self,
+        keys: &'a [Pubkey],
+    ) -> FetchFuture<'a, Vec<Option<Account>>> {
+        Box::pin(async move {
+            self.batches.lock().unwrap().push(keys.len());
+            self.requests.lock().unwrap().push(keys.to_vec());
+            Ok(keys.iter().map(|key| self.account(key)).collect())
+        })
+    }
+
+    fn get_multiple_accounts_with_commitment<'a>(
+        &'a self,
+        keys: &'a [Pubkey],
+        _commitment: CommitmentConfig,
+    ) -> FetchFuture<'a, Vec<Option<Account>>> {
+        Box::pin(async move {
+            self.requests.lock().unwrap().push(keys.to_vec());
+            Ok(keys.iter().map(|key| self.account(key)).collect())
+        })
+    }
+
+    fn get_account_with_commitment<'a>(
+        &'a self,
+        key: &'a Pubkey,
+        _commitment: CommitmentConfig,
+    ) -> FetchFuture<'a, Option<Account>> {
+        Box::pin(async move { Ok(self.account(key)) })
+    }
+
+    fn get_account_with_slot<'a>(
+        &'a self,
+        key: &'a Pubkey,
+        _commitment: CommitmentConfig,
+    ) -> FetchFuture<'a, (u64, Option<Account>)> {
+        Box::pin(async move { Ok((0, self.account(key))) })
+    }
+}
@@ -6,3 +6,46 @@ pub mod queue;
 pub use queue::*;
 pub mod oracle;
 pub use oracle::*;
+pub mod randomness;
+pub use randomness::*;
+pub mod oracle_stats;
+pub use oracle_stats::*;
+
+use crate::get_account_discriminator;
+use anyhow_ext::anyhow;
+use anyhow_ext::Error as AnyhowError;
+
+/// Checks that `data` starts with the Anchor discriminator of the `name`
+/// account and returns the account body following it.
+pub fn check_account_discriminator<'a>(data: &'a [u8], name: &str) -> Result<&'a [u8], AnyhowError> {
+    if data.len() < 8 {
+        return Err(anyhow!("{}: Account data too short", name));
+    }
+    let (discriminator, body) = data.split_at(8);
+    if discriminator != get_account_discriminator(name).as_slice() {
+        return Err(anyhow!(
+            "{}: Invalid account discriminator {}, not a {} account",
+            name,
+            hex::encode(discriminator),
+            name
+        ));
+    }
+    Ok(body)
+}
+
+/// Reads a `T` from raw `name` account data, including the 8 byte discriminator,
+/// checking both the discriminator and the account size.
+pub fn parse_account<T: bytemuck::Pod>(data: &[u8], name: &str) -> Result<T, AnyhowError> {
+    let body = check_account_discriminator(data, name)?;
+    let expected = std::mem::size_of::<T>();
+    if body.len() != expected {
+        return Err(anyhow!(
+            "{}: Invalid account size {}, expected {}",
+            name,
+            data.len(),
+            8 + expected
+        ));
+    }
+    bytemuck::try_pod_read_unaligned::<T>(body)
+        .map_err(|e| anyhow!("{}: Failed to parse account: {:?}", name, e))
+}
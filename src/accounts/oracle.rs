@@ -1,4 +1,5 @@
 use crate::*;
+use anyhow_ext::Error as AnyhowError;
 use bytemuck;
 use solana_sdk::pubkey::Pubkey;
 
@@ -76,15 +77,85 @@ pub struct Quote {
     _ebuf1: [u8; 512],
 }
 
+/// The verification status of an enclave quote.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum VerificationStatus {
+    None,
+    VerificationPending,
+    VerificationFailure,
+    VerificationSuccess,
+    Override,
+}
+
+impl From<u8> for VerificationStatus {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => VerificationStatus::VerificationPending,
+            2 => VerificationStatus::VerificationFailure,
+            4 => VerificationStatus::VerificationSuccess,
+            8 => VerificationStatus::Override,
+            _ => VerificationStatus::None,
+        }
+    }
+}
+
+impl Quote {
+    /// The decoded `verification_status`
+    pub fn status(&self) -> VerificationStatus {
+        self.verification_status.into()
+    }
+
+    /// Whether the quote can currently be trusted: it was verified
+    /// successfully and `now` is before `valid_until`
+    pub fn is_valid(&self, now: i64) -> bool {
+        self.status() == VerificationStatus::VerificationSuccess && now < self.valid_until
+    }
+
+    /// Whether the queue authority overrode the quote's verification. An
+    /// override carries no expiry, so it is not reported by `is_valid`.
+    pub fn is_overridden(&self) -> bool {
+        self.status() == VerificationStatus::Override
+    }
+
+    /// The enclave's ed25519 signer
+    pub fn ed25519_signer(&self) -> Pubkey {
+        self.enclave_signer
+    }
+
+    /// The enclave's 64 byte uncompressed secp256k1 signer as lowercase hex,
+    /// without the `04` prefix
+    pub fn secp256k1_signer_hex(&self) -> String {
+        hex::encode(self.secp256k1_signer)
+    }
+
+    /// The previous secp256k1 signer, see `secp256k1_signer_hex`
+    pub fn last_secp256k1_signer_hex(&self) -> String {
+        hex::encode(self.last_secp256k1_signer)
+    }
+}
+
 impl OracleAccountData {
     pub fn stats_key(oracle: &Pubkey) -> Pubkey {
-        Pubkey::find_program_address(
-            &[ORACLE_STATS_SEED, &oracle.to_bytes()],
-            &SWITCHBOARD_ON_DEMAND_PROGRAM_ID,
-        )
-        .0
+        Self::stats_key_with_program_id(oracle, &SWITCHBOARD_ON_DEMAND_PROGRAM_ID)
     }
 
+    /// The oracle stats address under a custom program ID, e.g. a localnet deployment
+    pub fn stats_key_with_program_id(oracle: &Pubkey, program_id: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[ORACLE_STATS_SEED, &oracle.to_bytes()], program_id).0
+    }
+
+    /// Whether the oracle's attestation is still fresh for `queue`: the quote
+    /// must be valid at `now` and verified within the queue's
+    /// `max_quote_verification_age`. A quote past its `valid_until` is not
+    /// fresh, even if it was verified within the queue's age window.
+    pub fn is_attestation_fresh(&self, queue: &QueueAccountData, now: i64) -> bool {
+        if !self.enclave.is_valid(now) {
+            return false;
+        }
+        now.saturating_sub(self.enclave.verification_timestamp) <= queue.max_quote_verification_age
+    }
+
+    /// The oracle's gateway URI, normalized by `normalize_gateway_uri`
     pub fn gateway_uri(&self) -> Option<String> {
         let uri = self.gateway_uri;
         let uri = String::from_utf8_lossy(&uri);
@@ -92,6 +163,7 @@ impl OracleAccountData {
             .split_at(uri.find('\0').unwrap_or(uri.len()))
             .0
             .to_string();
+        let uri = normalize_gateway_uri(&uri);
         if uri.is_empty() {
             return None;
         }
@@ -100,7 +172,126 @@ impl OracleAccountData {
 }
 
 impl LutOwner for OracleAccountData {
+    const ACCOUNT_NAME: &'static str = "OracleAccountData";
+
     fn lut_slot(&self) -> u64 {
         self.lut_slot
     }
 }
+
+impl TryFrom<&[u8]> for OracleAccountData {
+    type Error = AnyhowError;
+
+    /// Parses raw oracle account data, including the 8 byte discriminator
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        parse_account(data, "OracleAccountData")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+
+    fn quote(status: u8, valid_until: i64) -> Quote {
+        let mut quote = Quote::zeroed();
+        quote.verification_status = status;
+        quote.valid_until = valid_until;
+        quote
+    }
+
+    #[test]
+    fn status_decodes_every_value() {
+        assert_eq!(quote(0, 0).status(), VerificationStatus::None);
+        assert_eq!(
+            quote(1, 0).status(),
+            VerificationStatus::VerificationPending
+        );
+        assert_eq!(
+            quote(2, 0).status(),
+            VerificationStatus::VerificationFailure
+        );
+        assert_eq!(
+            quote(4, 0).status(),
+            VerificationStatus::VerificationSuccess
+        );
+        assert_eq!(quote(8, 0).status(), VerificationStatus::Override);
+        assert_eq!(quote(3, 0).status(), VerificationStatus::None);
+    }
+
+    #[test]
+    fn only_unexpired_successes_are_valid() {
+        let now = 1_700_000_000;
+        // (status, valid before expiry, valid at expiry, valid after expiry)
+        let cases = [
+            (0, false, false, false),
+            (1, false, false, false),
+            (2, false, false, false),
+            (4, true, false, false),
+            (8, false, false, false),
+        ];
+        for (status, before, at, after) in cases {
+            assert_eq!(
+                quote(status, now + 1).is_valid(now),
+                before,
+                "status {}",
+                status
+            );
+            assert_eq!(quote(status, now).is_valid(now), at, "status {}", status);
+            assert_eq!(
+                quote(status, now - 1).is_valid(now),
+                after,
+                "status {}",
+                status
+            );
+        }
+    }
+
+    #[test]
+    fn override_is_reported_separately() {
+        assert!(quote(8, 0).is_overridden());
+        assert!(!quote(4, i64::MAX).is_overridden());
+    }
+
+    fn oracle(verification_timestamp: i64, valid_until: i64) -> OracleAccountData {
+        let mut oracle = OracleAccountData::zeroed();
+        oracle.enclave = quote(4, valid_until);
+        oracle.enclave.verification_timestamp = verification_timestamp;
+        oracle
+    }
+
+    fn queue(max_quote_verification_age: i64) -> QueueAccountData {
+        let mut queue = QueueAccountData::zeroed();
+        queue.max_quote_verification_age = max_quote_verification_age;
+        queue
+    }
+
+    #[test]
+    fn attestation_fresh_within_age_and_before_expiry() {
+        let now = 1_700_000_000;
+        let queue = queue(600);
+        assert!(oracle(now - 10, now + 3600).is_attestation_fresh(&queue, now));
+        // Verified exactly max_quote_verification_age ago is still fresh
+        assert!(oracle(now - 600, now + 3600).is_attestation_fresh(&queue, now));
+        assert!(!oracle(now - 601, now + 3600).is_attestation_fresh(&queue, now));
+    }
+
+    #[test]
+    fn attestation_not_fresh_once_expired() {
+        let now = 1_700_000_000;
+        let queue = queue(600);
+        // Expiring at `now` is already expired
+        assert!(!oracle(now - 10, now).is_attestation_fresh(&queue, now));
+        assert!(oracle(now - 10, now + 1).is_attestation_fresh(&queue, now));
+        // Past valid_until, even though verified within the queue's age
+        assert!(!oracle(now - 10, now - 5).is_attestation_fresh(&queue, now));
+    }
+
+    #[test]
+    fn attestation_not_fresh_unless_verified() {
+        let now = 1_700_000_000;
+        let mut oracle = oracle(now - 10, now + 3600);
+        oracle.enclave.verification_status = 8;
+        assert!(!oracle.is_attestation_fresh(&queue(600), now));
+    }
+}
@@ -0,0 +1,144 @@
+use crate::*;
+use anyhow_ext::anyhow;
+use anyhow_ext::Error as AnyhowError;
+use bytemuck;
+use solana_sdk::pubkey::Pubkey;
+
+/// An oracle's reward accounting for a single epoch.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct OracleEpochInfo {
+    /// The epoch number.
+    pub id: u64,
+    _reserved1: u64,
+    /// The last slot of the epoch.
+    pub slot_end: u64,
+    /// Penalty accrued for missed or invalid submissions.
+    pub slash_score: u64,
+    /// Reward accrued for valid submissions.
+    pub reward_score: u64,
+    /// The oracle's stake weight for the epoch.
+    pub stake_score: u64,
+}
+
+/// An oracle's signature count over the current mega slot window.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct MegaSlotInfo {
+    _reserved1: u64,
+    /// The last slot of the window.
+    pub slot_end: u64,
+    /// The number of signatures the oracle is expected to produce in the window.
+    pub perf_goal: i64,
+    /// The number of signatures the oracle has produced so far in the window.
+    pub current_signature_count: i64,
+}
+
+/// The submission history and reward accounting of an oracle, written by
+/// every feed update it signs. Derived from `ORACLE_STATS_SEED`, see
+/// `OracleAccountData::stats_key`.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct OracleStatsAccountData {
+    /// The authority permitted to withdraw the oracle's rewards.
+    pub owner: Pubkey,
+    /// The oracle these stats belong to.
+    pub oracle: Pubkey,
+    /// The last epoch whose rewards have been settled.
+    pub finalized_epoch: OracleEpochInfo,
+    /// The epoch currently accruing.
+    pub current_epoch: OracleEpochInfo,
+    pub mega_slot_info: MegaSlotInfo,
+    /// The slot of the last reward transfer to the oracle.
+    pub last_transfer_slot: u64,
+    pub bump: u8,
+    _padding1: [u8; 7],
+    _ebuf: [u8; 1024],
+}
+// Guard the on-chain layout against accidental field changes
+const _: () = assert!(std::mem::size_of::<OracleStatsAccountData>() == 1232);
+
+impl OracleStatsAccountData {
+    pub fn size() -> usize {
+        8 + std::mem::size_of::<OracleStatsAccountData>()
+    }
+
+    /// Loads the stats account of `oracle`
+    pub async fn load(
+        client: &impl AccountFetcher,
+        oracle: &Pubkey,
+    ) -> Result<OracleStatsAccountData, AnyhowError> {
+        let key = OracleAccountData::stats_key(oracle);
+        let account = client
+            .get_account_data(&key)
+            .await
+            .map_err(|_| anyhow!("OracleStatsAccountData.load: Account not found"))?;
+        Self::parse(&account)
+    }
+
+    /// Parses raw oracle stats account data, including the 8 byte discriminator
+    pub fn parse(data: &[u8]) -> Result<OracleStatsAccountData, AnyhowError> {
+        parse_account(data, "OracleStatsAccountData")
+    }
+}
+
+/// An oracle's submission history for a single feed. Derived from
+/// `ORACLE_FEED_STATS_SEED`, see `FeedStatsAccountData::key`.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct FeedStatsAccountData {
+    /// The feed these stats belong to.
+    pub feed: Pubkey,
+    /// The oracle these stats belong to.
+    pub oracle: Pubkey,
+    /// The slot of the oracle's last accepted submission to the feed.
+    pub last_submission_slot: u64,
+    /// The number of accepted submissions the oracle has made to the feed.
+    pub submission_count: u64,
+    /// The rewards the oracle has earned from the feed, in lamports.
+    pub total_reward: u64,
+    pub bump: u8,
+    _padding1: [u8; 7],
+    _ebuf: [u8; 256],
+}
+// Guard the on-chain layout against accidental field changes
+const _: () = assert!(std::mem::size_of::<FeedStatsAccountData>() == 352);
+
+impl FeedStatsAccountData {
+    pub fn size() -> usize {
+        8 + std::mem::size_of::<FeedStatsAccountData>()
+    }
+
+    /// The stats address of `oracle` for `feed`
+    pub fn key(feed: &Pubkey, oracle: &Pubkey) -> Pubkey {
+        Self::key_with_program_id(feed, oracle, &SWITCHBOARD_ON_DEMAND_PROGRAM_ID)
+    }
+
+    /// The feed stats address under a custom program ID, e.g. a localnet deployment
+    pub fn key_with_program_id(feed: &Pubkey, oracle: &Pubkey, program_id: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[ORACLE_FEED_STATS_SEED, &feed.to_bytes(), &oracle.to_bytes()],
+            program_id,
+        )
+        .0
+    }
+
+    /// Loads the stats of `oracle` for `feed`
+    pub async fn load(
+        client: &impl AccountFetcher,
+        feed: &Pubkey,
+        oracle: &Pubkey,
+    ) -> Result<FeedStatsAccountData, AnyhowError> {
+        let key = Self::key(feed, oracle);
+        let account = client
+            .get_account_data(&key)
+            .await
+            .map_err(|_| anyhow!("FeedStatsAccountData.load: Account not found"))?;
+        Self::parse(&account)
+    }
+
+    /// Parses raw feed stats account data, including the 8 byte discriminator
+    pub fn parse(data: &[u8]) -> Result<FeedStatsAccountData, AnyhowError> {
+        parse_account(data, "FeedStatsAccountData")
+    }
+}
@@ -1,28 +1,38 @@
 use crate::*;
+use anyhow_ext::Error as AnyhowError;
 use bytemuck;
+use crate::serde_helpers::{hex_bytes, i128_string, nul_padded_string, pubkey_string};
 use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 use solana_sdk::pubkey::Pubkey;
 
 pub const PRECISION: u32 = 18;
 pub const MAX_SAMPLES: usize = 32;
 
 #[repr(C)]
-#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable, Serialize, Deserialize)]
 pub struct CurrentResult {
     /// The median value of the submissions needed for quorom size
+    #[serde(with = "i128_string")]
     pub value: i128,
     /// The standard deviation of the submissions needed for quorom size
+    #[serde(with = "i128_string")]
     pub std_dev: i128,
     /// The mean of the submissions needed for quorom size
+    #[serde(with = "i128_string")]
     pub mean: i128,
     /// The range of the submissions needed for quorom size
+    #[serde(with = "i128_string")]
     pub range: i128,
     /// The minimum value of the submissions needed for quorom size
+    #[serde(with = "i128_string")]
     pub min_value: i128,
     /// The maximum value of the submissions needed for quorom size
+    #[serde(with = "i128_string")]
     pub max_value: i128,
     /// The number of samples used to calculate this result
     pub num_samples: u8,
+    #[serde(skip)]
     pub padding1: [u8; 7],
     /// The slot at which this value was signed.
     pub slot: u64,
@@ -62,6 +72,19 @@ impl CurrentResult {
         Decimal::from_i128_with_scale(self.max_value, PRECISION)
     }
 
+    /// The band `mean ± k * std_dev`, saturating at `Decimal`'s bounds
+    pub fn confidence_interval(&self, k: Decimal) -> (Decimal, Decimal) {
+        let mean = self.mean();
+        let width = self.std_dev().saturating_mul(k.abs());
+        (mean.saturating_sub(width), mean.saturating_add(width))
+    }
+
+    /// Whether `value` lies within `confidence_interval(k)`, bounds included
+    pub fn is_within_band(&self, value: Decimal, k: Decimal) -> bool {
+        let (lower, upper) = self.confidence_interval(k);
+        lower <= value && value <= upper
+    }
+
     pub fn result_slot(&self) -> u64 {
         self.slot
     }
@@ -76,51 +99,73 @@ impl CurrentResult {
 }
 
 #[repr(C)]
-#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable, Serialize, Deserialize)]
 pub struct OracleSubmission {
     /// The public key of the oracle that submitted this value.
+    #[serde(with = "pubkey_string")]
     pub oracle: Pubkey,
     /// The slot at which this value was signed.
     pub slot: u64,
+    #[serde(skip)]
     pub padding1: [u8; 8],
     /// The value that was submitted.
+    #[serde(with = "i128_string")]
     pub value: i128,
 }
 
 /// A representation of the data in a pull feed account.
 #[repr(C)]
-#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable, Serialize, Deserialize)]
 pub struct PullFeedAccountData {
     /// The oracle submissions for this feed.
     pub submissions: [OracleSubmission; 32],
     /// The public key of the authority that can update the feed hash that
     /// this account will use for registering updates.
+    #[serde(with = "pubkey_string")]
     pub authority: Pubkey,
     /// The public key of the queue which oracles must be bound to in order to
     /// submit data to this feed.
+    #[serde(with = "pubkey_string")]
     pub queue: Pubkey,
     /// SHA-256 hash of the job schema oracles will execute to produce data
     /// for this feed.
+    #[serde(with = "hex_bytes")]
     pub feed_hash: [u8; 32],
     /// The slot at which this account was initialized.
     pub initialized_at: i64,
     pub permissions: u64,
     pub max_variance: u64,
     pub min_responses: u32,
+    #[serde(with = "nul_padded_string")]
     pub name: [u8; 32],
+    #[serde(skip)]
     _padding1: [u8; 3],
     pub min_sample_size: u8,
     pub last_update_timestamp: i64,
     pub lut_slot: u64,
+    #[serde(with = "hex_bytes")]
     pub ipfs_hash: [u8; 32], // deprecated
     pub result: CurrentResult,
     pub max_staleness: u32,
+    #[serde(skip)]
     _ebuf4: [u8; 20],
+    #[serde(skip)]
     _ebuf3: [u8; 24],
+    #[serde(skip, default = "crate::serde_helpers::zeroed")]
     _ebuf2: [u8; 256],
+    #[serde(skip, default = "crate::serde_helpers::zeroed")]
     _ebuf1: [u8; 512],
 }
 
+/// The age of a feed's current result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeedAge {
+    /// Slots elapsed since the result was signed
+    pub slots_elapsed: u64,
+    /// Seconds elapsed since the feed was last updated
+    pub seconds_elapsed: i64,
+}
+
 impl OracleSubmission {
     pub fn is_empty(&self) -> bool {
         self.slot == 0
@@ -159,10 +204,83 @@ impl PullFeedAccountData {
     pub fn feed_hash(&self) -> String {
         hex::encode(self.feed_hash)
     }
+
+    /// The feed name, truncated at the first NUL byte
+    pub fn name_string(&self) -> String {
+        let name = String::from_utf8_lossy(&self.name);
+        name.split_at(name.find('\0').unwrap_or(name.len()))
+            .0
+            .to_string()
+    }
+
+    /// The values of the non-empty submissions
+    fn submission_values(&self) -> Vec<Decimal> {
+        self.submissions
+            .iter()
+            .filter(|x| !x.is_empty())
+            .map(|x| x.value())
+            .collect()
+    }
+
+    /// The non-empty submissions signed within `max_age` slots of `current_slot`
+    pub fn valid_submissions(&self, current_slot: u64, max_age: u64) -> Vec<&OracleSubmission> {
+        self.submissions
+            .iter()
+            .filter(|x| !x.is_empty() && current_slot.saturating_sub(x.slot) <= max_age)
+            .collect()
+    }
+
+    /// The number of non-empty submissions signed within `max_age` slots of `current_slot`
+    pub fn num_valid_submissions(&self, current_slot: u64, max_age: u64) -> usize {
+        self.valid_submissions(current_slot, max_age).len()
+    }
+
+    /// The median of the non-empty submissions, computed locally
+    pub fn compute_median(&self) -> Option<Decimal> {
+        Aggregation::Median.apply(&self.submission_values())
+    }
+
+    /// The mean of the non-empty submissions, computed locally
+    pub fn compute_mean(&self) -> Option<Decimal> {
+        Aggregation::Mean.apply(&self.submission_values())
+    }
+
+    /// Whether the current result is older than `max_staleness` slots.
+    /// A feed that has never been updated is always considered stale.
+    pub fn is_stale(&self, current_slot: u64) -> bool {
+        if self.result.slot == 0 {
+            return true;
+        }
+        current_slot.saturating_sub(self.result.slot) > self.max_staleness as u64
+    }
+
+    /// The number of seconds elapsed since the last update, given the current unix timestamp
+    pub fn seconds_since_update(&self, now: i64) -> i64 {
+        now.saturating_sub(self.last_update_timestamp).max(0)
+    }
+
+    /// The age of the current result in slots and seconds
+    pub fn value_age(&self, current_slot: u64, now: i64) -> FeedAge {
+        FeedAge {
+            slots_elapsed: current_slot.saturating_sub(self.result.slot),
+            seconds_elapsed: self.seconds_since_update(now),
+        }
+    }
 }
 
 impl LutOwner for PullFeedAccountData {
+    const ACCOUNT_NAME: &'static str = "PullFeedAccountData";
+
     fn lut_slot(&self) -> u64 {
         self.lut_slot
     }
 }
+
+impl TryFrom<&[u8]> for PullFeedAccountData {
+    type Error = AnyhowError;
+
+    /// Parses raw pull feed account data, including the 8 byte discriminator
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        parse_account(data, "PullFeedAccountData")
+    }
+}
@@ -1,12 +1,17 @@
 use crate::Gateway;
 use crate::LutOwner;
 use crate::OracleAccountData;
+use crate::parse_account;
+use crate::MAX_MULTIPLE_ACCOUNTS;
+use crate::NATIVE_MINT;
 use anyhow_ext::anyhow;
 use anyhow_ext::Error as AnyhowError;
 use bytemuck::{Pod, Zeroable};
 use futures::future::join_all;
-use solana_client::nonblocking::rpc_client::RpcClient;
+use rust_decimal::Decimal;
+use crate::AccountFetcher;
 use solana_sdk::pubkey::Pubkey;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
@@ -63,57 +68,143 @@ impl QueueAccountData {
         8 + std::mem::size_of::<QueueAccountData>()
     }
 
+    /// The mint oracle rewards are paid in. Queues created before `mint` was
+    /// introduced leave it zeroed and pay in wrapped SOL.
+    pub fn reward_mint(&self) -> Pubkey {
+        if self.mint == Pubkey::default() {
+            *NATIVE_MINT
+        } else {
+            self.mint
+        }
+    }
+
+    /// `oracle_min_stake` in tokens of a mint with `decimals` decimals, or
+    /// `None` if `decimals` exceeds `Decimal`'s maximum scale of 28
+    pub fn oracle_min_stake_amount(&self, decimals: u8) -> Option<Decimal> {
+        Decimal::try_from_i128_with_scale(self.oracle_min_stake as i128, decimals as u32).ok()
+    }
+
+    /// `reward` in tokens of a mint with `decimals` decimals, see
+    /// `oracle_min_stake_amount`
+    pub fn reward_amount(&self, decimals: u8) -> Option<Decimal> {
+        Decimal::try_from_i128_with_scale(self.reward as i128, decimals as u32).ok()
+    }
+
+    /// Fetches the decimals of `reward_mint`. Classic and Token-2022 mints
+    /// share the base layout, with `decimals` at byte 44.
+    pub async fn load_reward_mint_decimals(
+        &self,
+        client: &impl AccountFetcher,
+    ) -> Result<u8, AnyhowError> {
+        let mint = self.reward_mint();
+        let data = client
+            .get_account_data(&mint)
+            .await
+            .map_err(|_| anyhow!("QueueAccountData.load_reward_mint_decimals: Mint {} not found", mint))?;
+        data.get(44)
+            .copied()
+            .ok_or_else(|| anyhow!("QueueAccountData.load_reward_mint_decimals: Invalid mint data"))
+    }
+
     /// Loads the oracles currently in the queue.
     pub fn oracle_keys(&self) -> Vec<Pubkey> {
         self.oracle_keys[..self.oracle_keys_len as usize].to_vec()
     }
 
+    /// The enclave measurements the queue accepts from its oracles.
+    pub fn allowed_enclaves(&self) -> &[[u8; 32]] {
+        let len = (self.mr_enclaves_len as usize).min(self.mr_enclaves.len());
+        &self.mr_enclaves[..len]
+    }
+
+    /// Whether an oracle running `mr_enclave` is permitted on the queue.
+    pub fn is_enclave_allowed(&self, mr_enclave: &[u8; 32]) -> bool {
+        self.allowed_enclaves().contains(mr_enclave)
+    }
+
     /// Loads the QueueAccountData from the given key.
-    pub async fn load(client: &RpcClient, key: &Pubkey) -> Result<QueueAccountData, AnyhowError> {
+    pub async fn load(client: &impl AccountFetcher, key: &Pubkey) -> Result<QueueAccountData, AnyhowError> {
         let account = client.get_account_data(key).await?;
-        let buf = account[8..].to_vec();
-        let parsed: &QueueAccountData = bytemuck::try_from_bytes(&buf)
-            .map_err(|e| anyhow!("Failed to parse QueueAccountData: {:?}", e))?;
-        Ok(parsed.clone())
+        QueueAccountData::try_from(account.as_slice())
     }
 
     /// Fetches all oracle accounts from the oracle keys and returns them as a list of (Pubkey, OracleAccountData).
+    /// Oracles whose accounts are missing or fail to parse (e.g. closed or resized) are skipped.
     pub async fn fetch_oracle_accounts(
         &self,
-        client: &RpcClient,
+        client: &impl AccountFetcher,
     ) -> Result<Vec<(Pubkey, OracleAccountData)>, AnyhowError> {
         let keys = self.oracle_keys();
-        let accounts_data = client
-            .get_multiple_accounts(&keys)
-            .await?
-            .into_iter()
-            .map(|account| {
-                let buf = account.unwrap_or_default().data[8..].to_vec();
-                let oracle_account: &OracleAccountData = bytemuck::try_from_bytes(&buf).unwrap();
-                oracle_account.clone()
-            })
-            .collect::<Vec<_>>();
+        let chunks = join_all(
+            keys.chunks(MAX_MULTIPLE_ACCOUNTS)
+                .map(|chunk| client.get_multiple_accounts(chunk)),
+        )
+        .await;
+        let mut accounts = Vec::with_capacity(keys.len());
+        for chunk in chunks {
+            accounts.extend(chunk?);
+        }
         let result = keys
             .into_iter()
-            .zip(accounts_data.into_iter())
+            .zip(accounts.into_iter())
+            .filter_map(|(key, account)| {
+                let oracle_account = OracleAccountData::try_from(account?.data.as_slice()).ok()?;
+                Some((key, oracle_account))
+            })
             .collect::<Vec<_>>();
         Ok(result)
     }
 
-    /// Fetches all gateways from the oracle accounts and tests them to see if they are reachable.
+    /// Whether `oracle` heartbeated within the queue's `node_timeout` of `now`.
+    /// Queues without a node timeout treat every oracle as active.
+    pub fn is_oracle_active(&self, oracle: &OracleAccountData, now: i64) -> bool {
+        self.node_timeout <= 0 || now.saturating_sub(oracle.last_heartbeat) <= self.node_timeout
+    }
+
+    /// Returns the keys of the given oracles that have heartbeated recently.
+    pub fn active_oracle_keys(
+        &self,
+        oracles: &[(Pubkey, OracleAccountData)],
+        now: i64,
+    ) -> Vec<Pubkey> {
+        oracles
+            .iter()
+            .filter(|(_, oracle)| self.is_oracle_active(oracle, now))
+            .map(|(key, _)| *key)
+            .collect()
+    }
+
+    /// Fetches the oracle accounts that have heartbeated within `node_timeout` of `now`.
+    pub async fn fetch_active_oracle_accounts(
+        &self,
+        client: &impl AccountFetcher,
+        now: i64,
+    ) -> Result<Vec<(Pubkey, OracleAccountData)>, AnyhowError> {
+        let accounts = self
+            .fetch_oracle_accounts(client)
+            .await?
+            .into_iter()
+            .filter(|(_, oracle)| self.is_oracle_active(oracle, now))
+            .collect();
+        Ok(accounts)
+    }
+
+    /// Fetches all gateways from the active oracle accounts and tests them to see if they are reachable.
     /// Returns a list of reachable gateways.
     /// # Arguments
     /// * `client` - The RPC client to use for fetching the oracle accounts.
     /// # Returns
     /// A list of reachable gateways.
-    pub async fn fetch_gateways(&self, client: &RpcClient) -> Result<Vec<Gateway>, AnyhowError> {
+    pub async fn fetch_gateways(&self, client: &impl AccountFetcher) -> Result<Vec<Gateway>, AnyhowError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|x| x.as_secs() as i64)
+            .unwrap_or_default();
         let gateways = self
-            .fetch_oracle_accounts(&client)
+            .fetch_active_oracle_accounts(client, now)
             .await?
             .into_iter()
-            .map(|x| x.1)
-            .filter_map(|x| x.gateway_uri())
-            .map(|x| Gateway::new(x))
+            .filter_map(|x| Gateway::from_oracle(&x.1))
             .collect::<Vec<_>>();
         let mut test_futures = Vec::new();
         for gateway in gateways.iter() {
@@ -128,10 +219,140 @@ impl QueueAccountData {
         }
         Ok(good_gws)
     }
+
+    /// Like `fetch_gateways`, but probes every gateway concurrently and returns
+    /// the reachable ones sorted fastest first, along with their measured
+    /// latency. Gateways not answering within `timeout` are dropped.
+    /// # Arguments
+    /// * `client` - The RPC client to use for fetching the oracle accounts.
+    /// * `timeout` - How long to wait for each gateway
+    pub async fn fetch_gateways_ranked(
+        &self,
+        client: &impl AccountFetcher,
+        timeout: Duration,
+    ) -> Result<Vec<(Gateway, Duration)>, AnyhowError> {
+        Ok(self
+            .fetch_gateway_infos(client, timeout)
+            .await?
+            .into_iter()
+            .map(|x| (x.gateway, x.latency))
+            .collect())
+    }
+
+    /// Like `fetch_gateways_ranked`, keeping the oracle behind each gateway so
+    /// callers can filter on it, e.g. by `OracleAccountData::is_attestation_fresh`.
+    /// # Arguments
+    /// * `client` - The RPC client to use for fetching the oracle accounts.
+    /// * `timeout` - How long to wait for each gateway
+    pub async fn fetch_gateway_infos(
+        &self,
+        client: &impl AccountFetcher,
+        timeout: Duration,
+    ) -> Result<Vec<GatewayInfo>, AnyhowError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|x| x.as_secs() as i64)
+            .unwrap_or_default();
+        let candidates = self
+            .fetch_active_oracle_accounts(client, now)
+            .await?
+            .into_iter()
+            .filter_map(|(oracle, data)| Some((oracle, data, Gateway::from_oracle(&data)?)))
+            .collect::<Vec<_>>();
+        let latencies =
+            join_all(candidates.iter().map(|x| x.2.measure_latency(timeout))).await;
+        let mut infos: Vec<GatewayInfo> = candidates
+            .into_iter()
+            .zip(latencies)
+            .filter_map(|((oracle, oracle_data, gateway), latency)| {
+                Some(GatewayInfo {
+                    oracle,
+                    oracle_data,
+                    gateway,
+                    latency: latency?,
+                })
+            })
+            .collect();
+        infos.sort_by_key(|x| x.latency);
+        Ok(infos)
+    }
+}
+
+/// A reachable gateway and the oracle that operates it
+#[derive(Clone, Debug)]
+pub struct GatewayInfo {
+    pub oracle: Pubkey,
+    pub oracle_data: OracleAccountData,
+    pub gateway: Gateway,
+    /// The round trip time of the gateway's test endpoint
+    pub latency: Duration,
 }
 
 impl LutOwner for QueueAccountData {
+    const ACCOUNT_NAME: &'static str = "QueueAccountData";
+
     fn lut_slot(&self) -> u64 {
         self.lut_slot
     }
 }
+
+impl TryFrom<&[u8]> for QueueAccountData {
+    type Error = AnyhowError;
+
+    /// Parses raw queue account data, including the 8 byte discriminator
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        parse_account(data, "QueueAccountData")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account_fetcher::MockFetcher;
+    use crate::get_account_discriminator;
+
+    fn oracle_account_bytes() -> Vec<u8> {
+        let mut data = get_account_discriminator("OracleAccountData");
+        data.extend_from_slice(bytemuck::bytes_of(&OracleAccountData::zeroed()));
+        data
+    }
+
+    #[tokio::test]
+    async fn fetch_oracle_accounts_skips_truncated_accounts() {
+        let valid = Pubkey::new_unique();
+        let truncated = Pubkey::new_unique();
+        let valid_data = oracle_account_bytes();
+        let truncated_data = valid_data[..valid_data.len() - 16].to_vec();
+        assert!(OracleAccountData::try_from(valid_data.as_slice()).is_ok());
+        assert!(OracleAccountData::try_from(truncated_data.as_slice()).is_err());
+
+        let mut queue = QueueAccountData::zeroed();
+        queue.oracle_keys[0] = valid;
+        queue.oracle_keys[1] = truncated;
+        queue.oracle_keys_len = 2;
+        let mut fetcher = MockFetcher::default();
+        fetcher.accounts.insert(valid, valid_data);
+        fetcher.accounts.insert(truncated, truncated_data);
+
+        let oracles = queue.fetch_oracle_accounts(&fetcher).await.unwrap();
+        let keys: Vec<Pubkey> = oracles.iter().map(|x| x.0).collect();
+        assert_eq!(keys, vec![valid]);
+    }
+
+    #[tokio::test]
+    async fn fetch_oracle_accounts_batches_a_full_queue() {
+        let mut queue = QueueAccountData::zeroed();
+        let mut fetcher = MockFetcher::default();
+        for key in queue.oracle_keys.iter_mut() {
+            *key = Pubkey::new_unique();
+            fetcher.accounts.insert(*key, oracle_account_bytes());
+        }
+        queue.oracle_keys_len = 128;
+
+        let oracles = queue.fetch_oracle_accounts(&fetcher).await.unwrap();
+
+        assert_eq!(*fetcher.batches.lock().unwrap(), vec![MAX_MULTIPLE_ACCOUNTS, 28]);
+        let keys: Vec<Pubkey> = oracles.iter().map(|x| x.0).collect();
+        assert_eq!(keys, queue.oracle_keys());
+    }
+}
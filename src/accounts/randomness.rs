@@ -0,0 +1,255 @@
+use crate::*;
+use anyhow_ext::anyhow;
+use anyhow_ext::Context;
+use anyhow_ext::Error as AnyhowError;
+use associated_token_account::get_associated_token_address;
+use associated_token_account::NATIVE_MINT;
+use associated_token_account::SPL_TOKEN_PROGRAM_ID;
+use base64::{engine::general_purpose::STANDARD as base64, Engine as _};
+use bytemuck;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+
+/// A representation of the data in a randomness account.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct RandomnessAccountData {
+    /// The authority permitted to commit to and reveal this randomness.
+    pub authority: Pubkey,
+    /// The queue whose oracles serve this randomness.
+    pub queue: Pubkey,
+    /// The slothash the current request committed to.
+    pub seed_slothash: [u8; 32],
+    /// The slot of `seed_slothash`.
+    pub seed_slot: u64,
+    /// The oracle assigned to reveal the current request.
+    pub oracle: Pubkey,
+    /// The slot at which the value was last revealed.
+    pub reveal_slot: u64,
+    /// The revealed randomness.
+    pub value: [u8; 32],
+    _ebuf2: [u8; 96],
+    _ebuf1: [u8; 128],
+}
+// Guard the on-chain layout against accidental field changes
+const _: () = assert!(std::mem::size_of::<RandomnessAccountData>() == 400);
+
+impl RandomnessAccountData {
+    pub fn size() -> usize {
+        8 + std::mem::size_of::<RandomnessAccountData>()
+    }
+
+    /// Loads the randomness account at `key`
+    pub async fn load(
+        client: &impl AccountFetcher,
+        key: &Pubkey,
+    ) -> Result<RandomnessAccountData, AnyhowError> {
+        let account = client
+            .get_account_data(key)
+            .await
+            .map_err(|_| anyhow!("RandomnessAccountData.load: Account not found"))?;
+        Self::parse(&account)
+    }
+
+    /// Parses raw randomness account data, including the 8 byte discriminator
+    pub fn parse(data: &[u8]) -> Result<RandomnessAccountData, AnyhowError> {
+        parse_account(data, "RandomnessAccountData")
+    }
+
+    /// Whether the value for the current commitment has been revealed
+    pub fn is_revealed(&self) -> bool {
+        self.reveal_slot != 0 && self.reveal_slot >= self.seed_slot
+    }
+}
+
+/// The randomness stats account of `oracle`
+pub fn randomness_stats_key(oracle: &Pubkey) -> Pubkey {
+    randomness_stats_key_with_program_id(oracle, &SWITCHBOARD_ON_DEMAND_PROGRAM_ID)
+}
+
+/// The randomness stats account of `oracle` under a custom program ID
+pub fn randomness_stats_key_with_program_id(oracle: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[ORACLE_RANDOMNESS_STATS_SEED, &oracle.to_bytes()], program_id).0
+}
+
+/// Builds an instruction committing `randomness` to the most recent slothash.
+/// `oracle` is the queue oracle that will be asked to reveal the value.
+pub fn randomness_commit(
+    randomness: Pubkey,
+    queue: Pubkey,
+    oracle: Pubkey,
+    authority: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *SWITCHBOARD_ON_DEMAND_PROGRAM_ID,
+        accounts: RandomnessCommit {
+            randomness,
+            queue,
+            oracle,
+            recent_slothashes: solana_sdk::sysvar::slot_hashes::ID,
+            authority,
+        }
+        .to_account_metas(None),
+        data: RandomnessCommitParams {}.data(),
+    }
+}
+
+/// Builds an instruction revealing the value of a committed `randomness`
+/// account from a gateway's `RandomnessRevealResponse`.
+/// # Arguments
+/// * `randomness` - The randomness account key
+/// * `data` - The randomness account's data, naming the queue, oracle and authority
+/// * `payer` - Pays the oracle's reward
+/// * `reveal` - The gateway's signed reveal for the current commitment
+pub fn randomness_reveal(
+    randomness: Pubkey,
+    data: &RandomnessAccountData,
+    payer: Pubkey,
+    reveal: &RandomnessRevealResponse,
+) -> Result<Instruction, AnyhowError> {
+    let signature: [u8; 64] = base64
+        .decode(&reveal.signature)
+        .context("randomness_reveal: base64:decode failure")?
+        .try_into()
+        .map_err(|_| anyhow!("randomness_reveal: Invalid signature length"))?;
+    let value: [u8; 32] = reveal
+        .value
+        .clone()
+        .try_into()
+        .map_err(|_| anyhow!("randomness_reveal: Invalid value length"))?;
+    let recovery_id = u8::try_from(reveal.recovery_id)
+        .map_err(|_| anyhow!("randomness_reveal: Invalid recovery id"))?;
+    Ok(Instruction {
+        program_id: *SWITCHBOARD_ON_DEMAND_PROGRAM_ID,
+        accounts: RandomnessReveal {
+            randomness,
+            oracle: data.oracle,
+            queue: data.queue,
+            stats: randomness_stats_key(&data.oracle),
+            authority: data.authority,
+            payer,
+            recent_slothashes: solana_sdk::sysvar::slot_hashes::ID,
+            system_program: solana_sdk::system_program::ID,
+            reward_escrow: get_associated_token_address(&randomness, &NATIVE_MINT),
+            token_program: *SPL_TOKEN_PROGRAM_ID,
+            wrapped_sol_mint: *NATIVE_MINT,
+            program_state: State::key(),
+        }
+        .to_account_metas(None),
+        data: RandomnessRevealParams {
+            signature,
+            recovery_id,
+            value,
+        }
+        .data(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+
+    fn randomness_data() -> RandomnessAccountData {
+        let mut data = RandomnessAccountData::zeroed();
+        data.authority = Pubkey::new_unique();
+        data.queue = Pubkey::new_unique();
+        data.oracle = Pubkey::new_unique();
+        data
+    }
+
+    fn reveal() -> RandomnessRevealResponse {
+        RandomnessRevealResponse {
+            signature: base64.encode([9u8; 64]),
+            recovery_id: 1,
+            value: vec![7; 32],
+        }
+    }
+
+    #[test]
+    fn stats_key_uses_the_randomness_stats_seed() {
+        let oracle = Pubkey::new_unique();
+        let expected = Pubkey::find_program_address(
+            &[ORACLE_RANDOMNESS_STATS_SEED, oracle.as_ref()],
+            &SWITCHBOARD_ON_DEMAND_PROGRAM_ID,
+        )
+        .0;
+        assert_eq!(randomness_stats_key(&oracle), expected);
+        assert_ne!(
+            randomness_stats_key(&oracle),
+            OracleAccountData::stats_key(&oracle)
+        );
+    }
+
+    #[test]
+    fn commit_ix_targets_the_slothashes_sysvar() {
+        let keys: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let ix = randomness_commit(keys[0], keys[1], keys[2], keys[3]);
+        assert_eq!(ix.program_id, *SWITCHBOARD_ON_DEMAND_PROGRAM_ID);
+        let metas: Vec<Pubkey> = ix.accounts.iter().map(|x| x.pubkey).collect();
+        assert_eq!(
+            metas,
+            vec![
+                keys[0],
+                keys[1],
+                keys[2],
+                solana_sdk::sysvar::slot_hashes::ID,
+                keys[3]
+            ]
+        );
+        assert_eq!(ix.data, get_discriminator("randomness_commit"));
+    }
+
+    #[test]
+    fn reveal_ix_derives_its_accounts_from_the_randomness_data() {
+        let randomness = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let data = randomness_data();
+        let ix = randomness_reveal(randomness, &data, payer, &reveal()).unwrap();
+        assert_eq!(ix.program_id, *SWITCHBOARD_ON_DEMAND_PROGRAM_ID);
+        let metas: Vec<Pubkey> = ix.accounts.iter().map(|x| x.pubkey).collect();
+        assert_eq!(
+            metas,
+            vec![
+                randomness,
+                data.oracle,
+                data.queue,
+                randomness_stats_key(&data.oracle),
+                data.authority,
+                payer,
+                solana_sdk::sysvar::slot_hashes::ID,
+                solana_sdk::system_program::ID,
+                get_associated_token_address(&randomness, &NATIVE_MINT),
+                *SPL_TOKEN_PROGRAM_ID,
+                *NATIVE_MINT,
+                State::key(),
+            ]
+        );
+        let signers: Vec<Pubkey> = ix
+            .accounts
+            .iter()
+            .filter(|x| x.is_signer)
+            .map(|x| x.pubkey)
+            .collect();
+        assert_eq!(signers, vec![data.authority, payer]);
+        assert_eq!(ix.data[..8], get_discriminator("randomness_reveal"));
+        assert_eq!(ix.data[8..72], [9; 64]);
+        assert_eq!(ix.data[72], 1);
+        assert_eq!(ix.data[73..], [7; 32]);
+    }
+
+    #[test]
+    fn reveal_ix_rejects_malformed_responses() {
+        let data = randomness_data();
+        let key = Pubkey::new_unique();
+        let mut short_signature = reveal();
+        short_signature.signature = base64.encode([9u8; 63]);
+        assert!(randomness_reveal(key, &data, key, &short_signature).is_err());
+        let mut short_value = reveal();
+        short_value.value = vec![7; 31];
+        assert!(randomness_reveal(key, &data, key, &short_value).is_err());
+        let mut bad_recovery_id = reveal();
+        bad_recovery_id.recovery_id = 256;
+        assert!(randomness_reveal(key, &data, key, &bad_recovery_id).is_err());
+    }
+}
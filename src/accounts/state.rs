@@ -1,9 +1,14 @@
 use crate::SWITCHBOARD_ON_DEMAND_PROGRAM_ID;
+use anyhow_ext::anyhow;
+use anyhow_ext::Error as AnyhowError;
+use bytemuck;
+use crate::AccountFetcher;
+use crate::parse_account;
 use solana_sdk::pubkey::Pubkey;
 
 const STATE_SEED: &[u8] = b"STATE";
 
-#[derive(Copy, Clone)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 #[repr(C)]
 pub struct StateEpochInfo {
     pub id: u64,
@@ -11,7 +16,9 @@ pub struct StateEpochInfo {
     pub slot_end: u64,
 }
 
-#[derive(Copy, Clone)]
+/// All padding in `State` is explicit (`padding1`, `padding2` and the
+/// `_ebuf` reserves), which the `Pod` derive checks at compile time.
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 #[repr(C)]
 pub struct State {
     pub bump: u8,
@@ -42,12 +49,59 @@ pub struct State {
     _ebuf2: [u8; 512],
     _ebuf1: [u8; 1024],
 }
+// Guard the on-chain layout against accidental field changes
+const _: () = assert!(std::mem::size_of::<State>() == 2128);
+
 impl State {
     pub fn key() -> Pubkey {
-        Pubkey::find_program_address(&[STATE_SEED], &Self::pid()).0
+        Self::key_with_program_id(&Self::pid())
+    }
+
+    /// The program state address under a custom program ID, e.g. a localnet deployment
+    pub fn key_with_program_id(program_id: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[STATE_SEED], program_id).0
     }
 
     pub fn pid() -> Pubkey {
         *SWITCHBOARD_ON_DEMAND_PROGRAM_ID
     }
+
+    /// Loads the program state account.
+    pub async fn load(client: &impl AccountFetcher) -> Result<State, AnyhowError> {
+        let account = client
+            .get_account_data(&Self::key())
+            .await
+            .map_err(|_| anyhow!("State.load: Account not found"))?;
+        Self::parse(&account)
+    }
+
+    /// Parses raw program state account data, including the 8 byte discriminator
+    pub fn parse(data: &[u8]) -> Result<State, AnyhowError> {
+        State::try_from(data)
+    }
+
+    /// The fraction of the current epoch elapsed at `current_slot`, from 0 to 1.
+    /// The epoch is taken to span the `epoch_length` slots ending at
+    /// `current_epoch.slot_end`.
+    pub fn epoch_progress(&self, current_slot: u64) -> f64 {
+        if self.epoch_length == 0 {
+            return 1.0;
+        }
+        let remaining = self.slots_until_next_epoch(current_slot).min(self.epoch_length);
+        (self.epoch_length - remaining) as f64 / self.epoch_length as f64
+    }
+
+    /// The slots left in the current epoch at `current_slot`, 0 once it has ended
+    pub fn slots_until_next_epoch(&self, current_slot: u64) -> u64 {
+        self.current_epoch.slot_end.saturating_sub(current_slot)
+    }
+}
+
+impl TryFrom<&[u8]> for State {
+    type Error = AnyhowError;
+
+    /// Parses raw program state account data, including the 8 byte discriminator
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        parse_account(data, "State")
+    }
 }
@@ -1,5 +1,7 @@
 use lazy_static::lazy_static;
+use solana_sdk::instruction::{AccountMeta, Instruction};
 use solana_sdk::pubkey::Pubkey;
+use solana_sdk::system_program;
 use std::str::FromStr;
 
 lazy_static! {
@@ -54,6 +56,46 @@ pub fn get_associated_token_address_with_program_id(
     .0
 }
 
+/// Builds an instruction creating the associated token account of `wallet`
+/// for `mint` under the classic token program, succeeding if it already exists
+pub fn create_associated_token_account_idempotent(
+    payer: &Pubkey,
+    wallet: &Pubkey,
+    mint: &Pubkey,
+) -> Instruction {
+    create_associated_token_account_idempotent_with_program_id(
+        payer,
+        wallet,
+        mint,
+        &SPL_TOKEN_PROGRAM_ID,
+    )
+}
+
+/// Builds an instruction creating the associated token account of `wallet`
+/// for `mint` under `token_program_id`, e.g. Token-2022, succeeding if it
+/// already exists
+pub fn create_associated_token_account_idempotent_with_program_id(
+    payer: &Pubkey,
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    token_program_id: &Pubkey,
+) -> Instruction {
+    let ata = get_associated_token_address_with_program_id(wallet, mint, token_program_id);
+    Instruction {
+        program_id: *SPL_ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(ata, false),
+            AccountMeta::new_readonly(*wallet, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(*token_program_id, false),
+        ],
+        // AssociatedTokenAccountInstruction::CreateIdempotent
+        data: vec![1],
+    }
+}
+
 fn get_associated_token_address_and_bump_seed_internal(
     wallet_address: &Pubkey,
     token_mint_address: &Pubkey,
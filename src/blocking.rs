@@ -0,0 +1,149 @@
+//! Synchronous wrappers for scripts and CLI tools that don't run an async
+//! runtime. Account loaders run on `solana_client`'s blocking `RpcClient`;
+//! gateway and crossbar requests run on a small internal Tokio runtime.
+use crate::*;
+use anyhow_ext::Error as AnyhowError;
+use lazy_static::lazy_static;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::account::Account;
+#[cfg(not(feature = "solana_sdk_1_16"))]
+use solana_sdk::address_lookup_table::AddressLookupTableAccount;
+#[cfg(feature = "solana_sdk_1_16")]
+use solana_sdk::address_lookup_table_account::AddressLookupTableAccount;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use std::future::Future;
+use tokio::runtime::Runtime;
+
+lazy_static! {
+    static ref RUNTIME: Runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()
+        .expect("failed to build the blocking runtime");
+}
+
+/// Runs `future` to completion on the crate's internal runtime.
+/// Must not be called from within an async context, nor used to drive the
+/// blocking `RpcClient`, which runs its own runtime.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    RUNTIME.block_on(future)
+}
+
+/// Adapts the blocking `RpcClient` to `AccountFetcher`. Its futures complete
+/// on the first poll, so they are only driven by `futures::executor::block_on`.
+struct BlockingFetcher<'a>(&'a RpcClient);
+
+impl AccountFetcher for BlockingFetcher<'_> {
+    fn get_account_data<'a>(&'a self, key: &'a Pubkey) -> FetchFuture<'a, Vec<u8>> {
+        Box::pin(async move { Ok(self.0.get_account_data(key)?) })
+    }
+
+    fn get_multiple_accounts<'a>(
+        &'a self,
+        keys: &'a [Pubkey],
+    ) -> FetchFuture<'a, Vec<Option<Account>>> {
+        Box::pin(async move { Ok(self.0.get_multiple_accounts(keys)?) })
+    }
+
+    fn get_multiple_accounts_with_commitment<'a>(
+        &'a self,
+        keys: &'a [Pubkey],
+        commitment: CommitmentConfig,
+    ) -> FetchFuture<'a, Vec<Option<Account>>> {
+        Box::pin(async move {
+            Ok(self
+                .0
+                .get_multiple_accounts_with_commitment(keys, commitment)?
+                .value)
+        })
+    }
+
+    fn get_account_with_commitment<'a>(
+        &'a self,
+        key: &'a Pubkey,
+        commitment: CommitmentConfig,
+    ) -> FetchFuture<'a, Option<Account>> {
+        Box::pin(async move { Ok(self.0.get_account_with_commitment(key, commitment)?.value) })
+    }
+
+    fn get_account_with_slot<'a>(
+        &'a self,
+        key: &'a Pubkey,
+        commitment: CommitmentConfig,
+    ) -> FetchFuture<'a, (u64, Option<Account>)> {
+        Box::pin(async move {
+            let response = self.0.get_account_with_commitment(key, commitment)?;
+            Ok((response.context.slot, response.value))
+        })
+    }
+
+    fn commitment(&self) -> CommitmentConfig {
+        self.0.commitment()
+    }
+}
+
+impl PullFeed {
+    pub fn load_data_blocking(
+        client: &RpcClient,
+        key: &Pubkey,
+    ) -> Result<PullFeedAccountData, AnyhowError> {
+        futures::executor::block_on(Self::load_data(&BlockingFetcher(client), key))
+    }
+}
+
+impl QueueAccountData {
+    pub fn load_blocking(client: &RpcClient, key: &Pubkey) -> Result<QueueAccountData, AnyhowError> {
+        futures::executor::block_on(Self::load(&BlockingFetcher(client), key))
+    }
+
+    pub fn fetch_oracle_accounts_blocking(
+        &self,
+        client: &RpcClient,
+    ) -> Result<Vec<(Pubkey, OracleAccountData)>, AnyhowError> {
+        futures::executor::block_on(self.fetch_oracle_accounts(&BlockingFetcher(client)))
+    }
+}
+
+impl State {
+    pub fn load_blocking(client: &RpcClient) -> Result<State, AnyhowError> {
+        futures::executor::block_on(Self::load(&BlockingFetcher(client)))
+    }
+}
+
+pub fn load_lookup_tables_blocking<T: LutOwner + bytemuck::Pod>(
+    client: &RpcClient,
+    keys: &[Pubkey],
+) -> Result<Vec<AddressLookupTableAccount>, AnyhowError> {
+    futures::executor::block_on(load_lookup_tables::<T>(&BlockingFetcher(client), keys))
+}
+
+impl Gateway {
+    pub fn fetch_signatures_from_encoded_blocking(
+        &self,
+        params: FetchSignaturesParams,
+    ) -> Result<FeedEvalResponseSingle, GatewayError> {
+        block_on(self.fetch_signatures_from_encoded(params))
+    }
+
+    pub fn fetch_signatures_multi_blocking(
+        &self,
+        params: FetchSignaturesMultiParams,
+    ) -> Result<FetchSignaturesMultiResponse, GatewayError> {
+        block_on(self.fetch_signatures_multi(params))
+    }
+}
+
+impl CrossbarClient {
+    pub fn fetch_blocking(&self, feed_hash: &str) -> Result<serde_json::Value, AnyhowError> {
+        block_on(self.fetch(feed_hash))
+    }
+
+    pub fn store_blocking(
+        &self,
+        queue_address: Pubkey,
+        jobs: &[serde_json::Value],
+    ) -> Result<StoreResponse, AnyhowError> {
+        block_on(self.store(queue_address, jobs))
+    }
+}
@@ -3,10 +3,22 @@ use anyhow_ext::anyhow;
 use anyhow_ext::Context;
 use anyhow_ext::Error as AnyhowError;
 use base58::ToBase58;
+use base64::{engine::general_purpose::STANDARD as base64, Engine as _};
 use reqwest::Client;
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::{Decimal, RoundingStrategy};
 use serde::{Deserialize, Serialize};
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::genesis_config::ClusterType;
+use std::sync::Arc;
+#[cfg(feature = "solana_sdk_1_16")]
+use solana_sdk::address_lookup_table_account::AddressLookupTableAccount;
+#[cfg(not(feature = "solana_sdk_1_16"))]
+use solana_sdk::address_lookup_table::AddressLookupTableAccount;
+use crate::decode_lookup_table;
+use crate::{HttpObserver, SharedHttpObserver};
+use crate::{get_discriminator, OracleResponse, PullFeedSubmitResponseParams, PRECISION};
+use borsh::BorshDeserialize;
 
 #[derive(Serialize, Deserialize)]
 pub struct StoreResponse {
@@ -23,6 +35,93 @@ pub struct FetchSolanaUpdatesResponse {
     pub lookupTables: Vec<String>,
 }
 
+impl FetchSolanaUpdatesResponse {
+    /// Converts the crossbar responses into `OracleResponse`s, ready for
+    /// `PullFeed::get_solana_submit_signatures_ix`. Crossbar only reports the
+    /// oracle signatures inside `pullIx`, so its base64 encoded submit
+    /// instruction data is decoded and paired with `responses` by position.
+    /// Submitted values are read from the signed submissions rather than the
+    /// lossy `f64` results.
+    /// # Returns
+    /// * `Result<(u64, Vec<OracleResponse>)>` - The slot the oracles signed
+    ///   against and their responses
+    pub fn oracle_responses(&self) -> Result<(u64, Vec<OracleResponse>), AnyhowError> {
+        let data = base64
+            .decode(&self.pullIx)
+            .context("FetchSolanaUpdatesResponse.oracle_responses: Invalid pullIx encoding")?;
+        if data.len() < 8 || data[..8] != get_discriminator("pull_feed_submit_response")[..] {
+            return Err(anyhow!(
+                "FetchSolanaUpdatesResponse.oracle_responses: pullIx is not a submit instruction"
+            ));
+        }
+        let params = PullFeedSubmitResponseParams::try_from_slice(&data[8..])
+            .context("FetchSolanaUpdatesResponse.oracle_responses: Invalid pullIx data")?;
+        if params.submissions.len() != self.responses.len() {
+            return Err(anyhow!(
+                "FetchSolanaUpdatesResponse.oracle_responses: {} submissions for {} responses",
+                params.submissions.len(),
+                self.responses.len()
+            ));
+        }
+        let responses = params
+            .submissions
+            .iter()
+            .zip(self.responses.iter())
+            .map(|(submission, resp)| {
+                let oracle = resp.oracle_key().ok_or_else(|| {
+                    anyhow!(
+                        "FetchSolanaUpdatesResponse.oracle_responses: Invalid oracle {}",
+                        resp.oracle
+                    )
+                })?;
+                let value = if submission.value == i128::MAX {
+                    None
+                } else {
+                    Decimal::try_from_i128_with_scale(submission.value, PRECISION).ok()
+                };
+                Ok(OracleResponse {
+                    value,
+                    error: resp.errors.clone(),
+                    oracle,
+                    signature: submission.signature,
+                    recovery_id: submission.recovery_id,
+                    timestamp: None,
+                })
+            })
+            .collect::<Result<Vec<_>, AnyhowError>>()?;
+        Ok((params.slot, responses))
+    }
+
+    /// Decode the base64 encoded lookup table accounts in `lookupTables`
+    /// # Arguments
+    /// * `keys` - The lookup table addresses, in the same order as `lookupTables`
+    /// # Returns
+    /// * `Result<Vec<AddressLookupTableAccount>>` - The decoded lookup tables,
+    ///   ready to be passed to a v0 message
+    pub fn lookup_table_accounts(
+        &self,
+        keys: &[Pubkey],
+    ) -> Result<Vec<AddressLookupTableAccount>, AnyhowError> {
+        if keys.len() != self.lookupTables.len() {
+            return Err(anyhow!(
+                "Expected {} lookup table keys, got {}",
+                self.lookupTables.len(),
+                keys.len()
+            ));
+        }
+        self.lookupTables
+            .iter()
+            .zip(keys.iter())
+            .map(|(encoded, key)| {
+                let data = base64
+                    .decode(encoded)
+                    .context("Failed to decode lookup table")?;
+                decode_lookup_table(*key, &data)
+            })
+            .collect()
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Response {
     pub oracle: String,
@@ -30,6 +129,25 @@ pub struct Response {
     pub errors: String,
 }
 
+impl Response {
+    /// The responding oracle, accepting either a base58 or hex encoded key
+    pub fn oracle_key(&self) -> Option<Pubkey> {
+        if let Ok(key) = self.oracle.parse::<Pubkey>() {
+            return Some(key);
+        }
+        let bytes: [u8; 32] = hex::decode(self.oracle.trim_start_matches("0x"))
+            .ok()?
+            .try_into()
+            .ok()?;
+        Some(Pubkey::new_from_array(bytes))
+    }
+
+    /// `result` as a `Decimal`, see `simulated_to_decimal`
+    pub fn value(&self) -> Option<Decimal> {
+        self.result.and_then(simulated_to_decimal)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SimulateSolanaFeedsResponse {
     pub feed: String,
@@ -37,17 +155,60 @@ pub struct SimulateSolanaFeedsResponse {
     pub results: Vec<Option<f64>>,
 }
 
+impl SimulateSolanaFeedsResponse {
+    /// `results` as `Decimal`s, see `simulated_to_decimal`
+    pub fn decimal_results(&self) -> Vec<Option<Decimal>> {
+        self.results
+            .iter()
+            .map(|x| x.and_then(simulated_to_decimal))
+            .collect()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SimulateFeedsResponse {
     pub feedHash: String,
     pub results: Vec<f64>,
 }
 
+impl SimulateFeedsResponse {
+    /// `results` as `Decimal`s, see `simulated_to_decimal`
+    pub fn decimal_results(&self) -> Vec<Option<Decimal>> {
+        self.results.iter().map(|x| simulated_to_decimal(*x)).collect()
+    }
+}
+
+/// Converts a simulated `f64` result to a `Decimal` comparable with on-chain
+/// values. The float is first read as the shortest decimal that round-trips
+/// to it, so e.g. `0.1` becomes exactly `0.1`, then rounded half-to-even to
+/// `PRECISION` decimal places. Returns `None` for NaN, infinities and
+/// magnitudes beyond `Decimal`'s range of about 7.9e28.
+///
+/// The result may still carry fewer decimals than a submission needs; check
+/// it with `fits_submission_precision`.
+pub fn simulated_to_decimal(value: f64) -> Option<Decimal> {
+    if !value.is_finite() {
+        return None;
+    }
+    let value = Decimal::from_f64(value)?;
+    Some(value.round_dp_with_strategy(PRECISION, RoundingStrategy::MidpointNearestEven))
+}
+
+/// Whether `value` can be submitted with the full `PRECISION` decimal places.
+/// `Decimal`'s 96-bit mantissa caps such values at about 7.9e10 in magnitude;
+/// larger values would be submitted as missing.
+pub fn fits_submission_precision(value: Decimal) -> bool {
+    let mut value = value;
+    value.rescale(PRECISION);
+    value.scale() == PRECISION
+}
+
 #[derive(Clone, Debug)]
 pub struct CrossbarClient {
     crossbar_url: String,
     verbose: bool,
     client: Client,
+    observer: SharedHttpObserver,
 }
 
 fn cluster_type_to_string(cluster_type: ClusterType) -> String {
@@ -73,9 +234,16 @@ impl CrossbarClient {
             crossbar_url: crossbar_url.to_string(),
             verbose,
             client: Client::new(),
+            observer: SharedHttpObserver::default(),
         }
     }
 
+    /// Reports every request this client makes to `observer`
+    pub fn with_observer(mut self, observer: Arc<dyn HttpObserver>) -> Self {
+        self.observer = SharedHttpObserver::new(observer);
+        self
+    }
+
     /// Fetch feed jobs from the crossbar gateway
     /// # Arguments
     /// * `feed_hash` - The feed hash of the jobs it performs
@@ -85,9 +253,8 @@ impl CrossbarClient {
     pub async fn fetch(&self, feed_hash: &str) -> Result<serde_json::Value, AnyhowError> {
         let url = format!("{}/fetch/{}", self.crossbar_url, feed_hash);
         let resp = self
-            .client
-            .get(&url)
-            .send()
+            .observer
+            .observe("crossbar.fetch", self.client.get(&url).send())
             .await
             .context("Failed to send fetch request")?;
 
@@ -115,12 +282,15 @@ impl CrossbarClient {
         let payload = serde_json::json!({ "queue": queue_hex, "jobs": jobs });
 
         let url = format!("{}/store", self.crossbar_url);
-        let resp = self
+        let request = self
             .client
             .post(&url)
             .json(&payload)
             .header("Content-Type", "application/json")
-            .send()
+            .send();
+        let resp = self
+            .observer
+            .observe("crossbar.store", request)
             .await
             .context("Failed to send store request")?;
 
@@ -160,7 +330,10 @@ impl CrossbarClient {
             url.push_str(&format!("?numSignatures={}", num_signatures));
         }
 
-        let resp = self.client.get(&url).send().await?;
+        let resp = self
+            .observer
+            .observe("crossbar.fetch_solana_updates", self.client.get(&url).send())
+            .await?;
 
         let status = resp.status();
         if !status.is_success() {
@@ -194,7 +367,10 @@ impl CrossbarClient {
             "{}/simulate/solana/{}/{}",
             self.crossbar_url, network, feeds_param
         );
-        let resp = self.client.get(&url).send().await?;
+        let resp = self
+            .observer
+            .observe("crossbar.simulate_solana_feeds", self.client.get(&url).send())
+            .await?;
 
         let status = resp.status();
         let raw = resp.text().await.context("Failed to fetch response")?;
@@ -222,9 +398,8 @@ impl CrossbarClient {
         let feeds_param = feed_hashes.join(",");
         let url = format!("{}/simulate/{}", self.crossbar_url, feeds_param);
         let resp = self
-            .client
-            .get(&url)
-            .send()
+            .observer
+            .observe("crossbar.simulate_feeds", self.client.get(&url).send())
             .await
             .context("Failed to send simulate feeds request")?;
 
@@ -243,3 +418,39 @@ impl CrossbarClient {
         Ok(resp.json().await.context("Failed to parse response")?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simulated_to_decimal_handles_extreme_magnitudes() {
+        assert_eq!(simulated_to_decimal(1.5), Some(Decimal::new(15, 1)));
+        // Below PRECISION decimal places rounds to zero
+        assert_eq!(simulated_to_decimal(1e-20), Some(Decimal::ZERO));
+        assert_eq!(simulated_to_decimal(1e-17), Some(Decimal::new(1, 17)));
+        assert_eq!(
+            simulated_to_decimal(1e20),
+            Some(Decimal::from_i128_with_scale(10i128.pow(20), 0))
+        );
+        // Beyond Decimal's range
+        assert_eq!(simulated_to_decimal(1e29), None);
+        assert_eq!(simulated_to_decimal(f64::MAX), None);
+        assert_eq!(simulated_to_decimal(f64::NAN), None);
+        assert_eq!(simulated_to_decimal(f64::INFINITY), None);
+        assert_eq!(simulated_to_decimal(f64::NEG_INFINITY), None);
+    }
+
+    #[test]
+    fn fits_submission_precision_bounds() {
+        assert!(fits_submission_precision(Decimal::new(15, 1)));
+        assert!(fits_submission_precision(Decimal::ZERO));
+        // The largest whole number with 18 decimals still fits a 96-bit mantissa
+        let largest = Decimal::from(79_228_162_514i64);
+        assert!(fits_submission_precision(largest));
+        assert!(!fits_submission_precision(largest + Decimal::ONE));
+        assert!(!fits_submission_precision(-largest - Decimal::ONE));
+        let huge = simulated_to_decimal(1e20).unwrap();
+        assert!(!fits_submission_precision(huge));
+    }
+}
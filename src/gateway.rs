@@ -1,11 +1,24 @@
 use crate::oracle_job::OracleJob;
+use crate::OracleAccountData;
+use crate::RateLimiter;
+use crate::{HttpObserver, SharedHttpObserver};
+use crate::SlotHash;
+use anyhow_ext::anyhow;
+use anyhow_ext::Error as AnyhowError;
 use base64::prelude::*;
+use futures::stream::{self, StreamExt, TryStreamExt};
 use prost::Message;
-use reqwest::header::CONTENT_TYPE;
+use reqwest::header::{CONTENT_TYPE, RETRY_AFTER};
 use reqwest::Client;
+use reqwest::ClientBuilder;
+use reqwest::Identity;
+use reqwest::{Response, StatusCode};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use solana_sdk::pubkey::Pubkey;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct FeedEvalResponse {
@@ -98,27 +111,173 @@ pub struct BridgeEnclaveResponse {
     pub recovery_id: i32,
 }
 
+/// An error from a gateway request
+#[derive(Debug)]
+pub enum GatewayError {
+    /// The request could not be sent or its response could not be read
+    Request(reqwest::Error),
+    /// The gateway answered HTTP 429. `retry_after` is taken from the
+    /// `Retry-After` header when it holds a number of seconds.
+    RateLimited { retry_after: Option<Duration> },
+    /// The response body is not the expected JSON
+    Decode(serde_json::Error),
+}
+
+impl std::fmt::Display for GatewayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GatewayError::Request(e) => write!(f, "Gateway request failed: {}", e),
+            GatewayError::RateLimited {
+                retry_after: Some(retry_after),
+            } => write!(f, "Gateway rate limited, retry after {:?}", retry_after),
+            GatewayError::RateLimited { retry_after: None } => write!(f, "Gateway rate limited"),
+            GatewayError::Decode(e) => write!(f, "Invalid gateway response: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for GatewayError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GatewayError::Request(e) => Some(e),
+            GatewayError::RateLimited { .. } => None,
+            GatewayError::Decode(e) => Some(e),
+        }
+    }
+}
+
+impl From<reqwest::Error> for GatewayError {
+    fn from(e: reqwest::Error) -> Self {
+        GatewayError::Request(e)
+    }
+}
+
+/// Reads a JSON gateway response, reporting HTTP 429 as `GatewayError::RateLimited`
+async fn parse_response<T: DeserializeOwned>(res: Response) -> Result<T, GatewayError> {
+    if res.status() == StatusCode::TOO_MANY_REQUESTS {
+        let retry_after = res
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|x| x.to_str().ok())
+            .and_then(|x| x.trim().parse::<u64>().ok())
+            .map(Duration::from_secs);
+        return Err(GatewayError::RateLimited { retry_after });
+    }
+    let raw = res.text().await?;
+    serde_json::from_str(&raw).map_err(GatewayError::Decode)
+}
+
+/// The maximum number of randomness reveal requests in flight per batch
+const RANDOMNESS_REVEAL_CONCURRENCY: usize = 8;
+
+/// Normalizes a gateway URI so request paths can be appended to it: trims
+/// whitespace and trailing slashes and defaults a missing scheme to `https://`.
+pub fn normalize_gateway_uri(uri: &str) -> String {
+    let uri = uri.trim().trim_end_matches('/');
+    if uri.is_empty() || uri.contains("://") {
+        uri.to_string()
+    } else {
+        format!("https://{}", uri)
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct Gateway {
     gateway_url: String,
     client: Arc<Client>,
+    observer: SharedHttpObserver,
+    rate_limiter: Option<Arc<RateLimiter>>,
 }
 
 impl Gateway {
-    pub fn new(gateway_url: String) -> Self {
-        let client = Client::builder()
+    fn client_builder() -> ClientBuilder {
+        Client::builder()
             .timeout(Duration::from_secs(10))
             // Switchboard does its own keypair authentication
             .danger_accept_invalid_certs(true)
-            .build()
-            .unwrap();
+    }
+
+    pub fn new(gateway_url: String) -> Self {
+        let client = Self::client_builder().build().unwrap();
 
         Self {
-            gateway_url,
+            gateway_url: normalize_gateway_uri(&gateway_url),
             client: Arc::new(client),
+            observer: SharedHttpObserver::default(),
+            rate_limiter: None,
         }
     }
 
+    /// Creates a gateway that presents `identity` as a TLS client certificate,
+    /// for gateways deployed behind an mTLS-gated ingress.
+    /// # Arguments
+    /// * `gateway_url` - The gateway url
+    /// * `identity` - The client certificate and key, e.g. from `Identity::from_pem`
+    pub fn with_identity(gateway_url: String, identity: Identity) -> Result<Self, reqwest::Error> {
+        let client = Self::client_builder().identity(identity).build()?;
+
+        Ok(Self {
+            gateway_url: normalize_gateway_uri(&gateway_url),
+            client: Arc::new(client),
+            observer: SharedHttpObserver::default(),
+            rate_limiter: None,
+        })
+    }
+
+    /// Reports every request this gateway makes to `observer`
+    pub fn with_observer(mut self, observer: Arc<dyn HttpObserver>) -> Self {
+        self.observer = SharedHttpObserver::new(observer);
+        self
+    }
+
+    /// Limits signature and randomness requests to `requests_per_second`,
+    /// allowing bursts of up to `burst`. Requests over the limit wait for a
+    /// token instead of being sent and rejected with HTTP 429.
+    pub fn with_rate_limit(mut self, requests_per_second: f64, burst: u32) -> Self {
+        self.rate_limiter = Some(Arc::new(RateLimiter::new(requests_per_second, burst)));
+        self
+    }
+
+    /// Like `with_rate_limit`, using the `rate_limit` the oracle advertises in
+    /// `ping` as both the rate per second and the burst. Gateways advertising
+    /// no limit are left unlimited.
+    pub async fn with_advertised_rate_limit(self) -> Result<Self, GatewayError> {
+        let ping = self.ping().await?;
+        if ping.rate_limit <= 0 {
+            return Ok(self);
+        }
+        Ok(self.with_rate_limit(ping.rate_limit as f64, ping.rate_limit as u32))
+    }
+
+    async fn wait_for_rate_limit(&self) {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire().await;
+        }
+    }
+
+    /// Fetches the oracle's identity and advertised capabilities
+    /// # Returns
+    /// * `Result<PingResponse, GatewayError>`
+    pub async fn ping(&self) -> Result<PingResponse, GatewayError> {
+        let url = format!("{}/gateway/api/v1/ping", self.gateway_url);
+        let body = serde_json::json!({ "api_version": "1.0.0" });
+
+        let request = self
+            .client
+            .post(&url)
+            .header(CONTENT_TYPE, "application/json")
+            .json(&body)
+            .send();
+        let res = self.observer.observe("gateway.ping", request).await?;
+
+        parse_response(res).await
+    }
+
+    /// The gateway advertised by `oracle`, or `None` if it has no gateway URI
+    pub fn from_oracle(oracle: &OracleAccountData) -> Option<Self> {
+        oracle.gateway_uri().map(Self::new)
+    }
+
     /// Fetches signatures from the gateway
     /// # Arguments
     /// * `params` - FetchSignaturesParams
@@ -129,11 +288,11 @@ impl Gateway {
     /// * `params.min_responses` - The minimum number of responses
     /// * `params.use_timestamp` - Whether to use the timestamp
     /// # Returns
-    /// * `Result<FeedEvalResponseSingle, reqwest::Error>`
+    /// * `Result<FeedEvalResponseSingle, GatewayError>`
     pub async fn fetch_signatures_from_encoded(
         &self,
         params: FetchSignaturesParams,
-    ) -> Result<FeedEvalResponseSingle, reqwest::Error> {
+    ) -> Result<FeedEvalResponseSingle, GatewayError> {
         let url = format!("{}/gateway/api/v1/fetch_signatures", self.gateway_url);
         let body = serde_json::json!({
             "api_version": "1.0.0",
@@ -147,18 +306,16 @@ impl Gateway {
             "use_timestamp": params.use_timestamp.unwrap_or(false),
         });
 
-        let res = self
+        self.wait_for_rate_limit().await;
+        let request = self
             .client
             .post(&url)
             .header(CONTENT_TYPE, "application/json")
             .json(&body)
-            .send()
-            .await?;
-
-        let raw = res.text().await?;
-        let res = serde_json::from_str::<FeedEvalResponseSingle>(&raw).unwrap();
+            .send();
+        let res = self.observer.observe("gateway.fetch_signatures", request).await?;
 
-        Ok(res)
+        parse_response(res).await
     }
 
     /// Fetches signatures from the gateway using the multi-feed method
@@ -169,11 +326,11 @@ impl Gateway {
     /// * `params.num_signatures` - The number of signatures to fetch
     /// * `params.use_timestamp` - Whether to use the timestamp
     /// # Returns
-    /// * `Result<FetchSignaturesMultiResponse, reqwest::Error>`
+    /// * `Result<FetchSignaturesMultiResponse, GatewayError>`
     pub async fn fetch_signatures_multi(
         &self,
         params: FetchSignaturesMultiParams,
-    ) -> Result<FetchSignaturesMultiResponse, reqwest::Error> {
+    ) -> Result<FetchSignaturesMultiResponse, GatewayError> {
         let url = format!("{}/gateway/api/v1/fetch_signatures_multi", self.gateway_url);
         let mut feed_requests = vec![];
 
@@ -196,23 +353,77 @@ impl Gateway {
             "feed_requests": feed_requests,
         });
 
-        let res = self
+        self.wait_for_rate_limit().await;
+        let request = self
+            .client
+            .post(&url)
+            .header(CONTENT_TYPE, "application/json")
+            .json(&body)
+            .send();
+        let res = self.observer.observe("gateway.fetch_signatures_multi", request).await?;
+
+        parse_response(res).await
+    }
+
+    /// Fetches the randomness reveal for a committed randomness account
+    /// # Arguments
+    /// * `randomness` - The randomness account
+    /// * `slothash` - The slothash the randomness account committed to
+    /// # Returns
+    /// * `Result<RandomnessRevealResponse, GatewayError>`
+    pub async fn fetch_randomness_reveal(
+        &self,
+        randomness: &Pubkey,
+        slothash: &SlotHash,
+    ) -> Result<RandomnessRevealResponse, GatewayError> {
+        let url = format!("{}/gateway/api/v1/randomness_reveal", self.gateway_url);
+        let body = serde_json::json!({
+            "slothash": slothash.hash.to_vec(),
+            "randomness_key": hex::encode(randomness.to_bytes()),
+            "slot": slothash.slot,
+        });
+
+        self.wait_for_rate_limit().await;
+        let request = self
             .client
             .post(&url)
             .header(CONTENT_TYPE, "application/json")
             .json(&body)
-            .send()
-            .await?;
-        let res = res.json::<FetchSignaturesMultiResponse>().await?;
+            .send();
+        let res = self.observer.observe("gateway.randomness_reveal", request).await?;
+
+        parse_response(res).await
+    }
 
-        Ok(res)
+    /// Fetches randomness reveals for many accounts, with a bounded number of
+    /// requests in flight. Results are returned in the order of `accounts`.
+    /// # Arguments
+    /// * `accounts` - The randomness accounts and the slothashes they committed to
+    /// # Returns
+    /// * `Result<Vec<RandomnessRevealResponse>, AnyhowError>`
+    pub async fn randomness_reveal_many(
+        &self,
+        accounts: &[(Pubkey, SlotHash)],
+    ) -> Result<Vec<RandomnessRevealResponse>, AnyhowError> {
+        stream::iter(accounts)
+            .map(|(randomness, slothash)| async move {
+                self.fetch_randomness_reveal(randomness, slothash)
+                    .await
+                    .map_err(|e| anyhow!("Failed to reveal randomness {}: {}", randomness, e))
+            })
+            .buffered(RANDOMNESS_REVEAL_CONCURRENCY)
+            .try_collect()
+            .await
     }
 
     pub async fn test_gateway(&self) -> bool {
         let client = &self.client;
         // Make HTTP request
         let url = format!("{}/gateway/api/v1/test", self.gateway_url);
-        let response = client.get(&url).send().await;
+        let response = self
+            .observer
+            .observe("gateway.test", client.get(&url).send())
+            .await;
 
         // Process response
         if let Ok(resp) = response {
@@ -225,6 +436,16 @@ impl Gateway {
             false
         }
     }
+
+    /// Measures the round trip time of a `test_gateway` probe, or `None` if the
+    /// gateway is unreachable or doesn't answer within `timeout`
+    pub async fn measure_latency(&self, timeout: Duration) -> Option<Duration> {
+        let started = Instant::now();
+        match tokio::time::timeout(timeout, self.test_gateway()).await {
+            Ok(true) => Some(started.elapsed()),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -258,3 +479,140 @@ pub fn encode_jobs(job_array: Vec<OracleJob>) -> Vec<String> {
         .map(|job| BASE64_STANDARD.encode(&job.encode_length_delimited_to_vec()))
         .collect()
 }
+
+/// Computes a feed's `feed_hash` the way Crossbar does when storing its jobs:
+/// the SHA-256 of the queue key followed by each length-delimited job.
+pub fn compute_feed_hash(queue: &Pubkey, jobs: &[OracleJob]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(queue.to_bytes());
+    for job in jobs {
+        hasher.update(job.encode_length_delimited_to_vec());
+    }
+    hasher.finalize().into()
+}
+
+/// Like `encode_jobs`, but first checks that every job has at least one task
+/// and that every task has its type set, so malformed jobs are reported here
+/// instead of being rejected by the gateway.
+pub fn try_encode_jobs(job_array: Vec<OracleJob>) -> Result<Vec<String>, AnyhowError> {
+    for (i, job) in job_array.iter().enumerate() {
+        if job.tasks.is_empty() {
+            return Err(anyhow!("try_encode_jobs: job {} has no tasks", i));
+        }
+        if let Some(j) = job.tasks.iter().position(|task| task.task.is_none()) {
+            return Err(anyhow!("try_encode_jobs: job {} task {} has no task type", i, j));
+        }
+    }
+    Ok(encode_jobs(job_array))
+}
+
+/// The inverse of `encode_jobs`: decodes base64, length-delimited jobs.
+pub fn decode_jobs(encoded: &[String]) -> Result<Vec<OracleJob>, AnyhowError> {
+    encoded
+        .iter()
+        .enumerate()
+        .map(|(i, job)| {
+            let bytes = BASE64_STANDARD
+                .decode(job)
+                .map_err(|e| anyhow!("decode_jobs: job {} is not valid base64: {}", i, e))?;
+            OracleJob::decode_length_delimited(bytes.as_slice())
+                .map_err(|e| anyhow!("decode_jobs: job {} is not a valid OracleJob: {}", i, e))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle_job::oracle_job::http_task::Method;
+    use crate::oracle_job_builder::{http_json_job, median_http_json_job, OracleJobBuilder};
+
+    #[test]
+    fn decode_jobs_inverts_encode_jobs() {
+        let jobs = vec![
+            http_json_job("https://api.example.com/price", "$.price").unwrap(),
+            OracleJobBuilder::new()
+                .http(
+                    "https://api.example.com/quote",
+                    Method::Post,
+                    &[("content-type", "application/json")],
+                    Some("{\"symbol\":\"SOL\"}"),
+                )
+                .json_parse("$.data.mid")
+                .build()
+                .unwrap(),
+            median_http_json_job(
+                &[
+                    ("https://a.example.com", "$.p"),
+                    ("https://b.example.com", "$.p"),
+                ],
+                Some(1),
+            )
+            .unwrap(),
+            OracleJob::default(),
+        ];
+        assert_eq!(decode_jobs(&encode_jobs(jobs.clone())).unwrap(), jobs);
+        assert!(decode_jobs(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn decode_jobs_rejects_malformed_input() {
+        assert!(decode_jobs(&["not base64!".to_string()]).is_err());
+        // Valid base64, but the length prefix claims more bytes than follow
+        assert!(decode_jobs(&[BASE64_STANDARD.encode([0x05, 0x0a])]).is_err());
+    }
+
+    #[test]
+    fn compute_feed_hash_depends_on_queue_and_job_order() {
+        use crate::oracle_job::oracle_job::value_task::Value;
+        use crate::oracle_job::oracle_job::{task, Task, ValueTask};
+
+        let value_job = |value: f64| OracleJob {
+            tasks: vec![Task {
+                task: Some(task::Task::ValueTask(ValueTask {
+                    value: Some(Value::Value(value)),
+                })),
+            }],
+        };
+        let queue = Pubkey::new_unique();
+        let jobs = vec![value_job(1.0), value_job(2.0)];
+        let hash = compute_feed_hash(&queue, &jobs);
+
+        assert_eq!(compute_feed_hash(&queue, &jobs), hash);
+        assert_ne!(compute_feed_hash(&Pubkey::new_unique(), &jobs), hash);
+        let reversed: Vec<OracleJob> = jobs.iter().rev().cloned().collect();
+        assert_ne!(compute_feed_hash(&queue, &reversed), hash);
+        // Without jobs only the queue is hashed
+        let queue_only: [u8; 32] = Sha256::digest(queue.to_bytes()).into();
+        assert_eq!(compute_feed_hash(&queue, &[]), queue_only);
+        // An empty job still contributes its zero length prefix
+        assert_ne!(
+            compute_feed_hash(&queue, &[OracleJob::default()]),
+            queue_only
+        );
+    }
+
+    #[test]
+    fn normalize_gateway_uri_cases() {
+        let cases = [
+            ("https://gw.example.com", "https://gw.example.com"),
+            ("https://gw.example.com/", "https://gw.example.com"),
+            ("https://gw.example.com//", "https://gw.example.com"),
+            ("gw.example.com", "https://gw.example.com"),
+            ("gw.example.com/", "https://gw.example.com"),
+            ("  gw.example.com/\n", "https://gw.example.com"),
+            ("http://10.0.0.1:8082/", "http://10.0.0.1:8082"),
+            (
+                "https://gw.example.com/mainnet/",
+                "https://gw.example.com/mainnet",
+            ),
+            // Only trailing slashes are trimmed
+            ("gw.example.com//mainnet", "https://gw.example.com//mainnet"),
+            ("", ""),
+            (" / ", ""),
+        ];
+        for (uri, expected) in cases {
+            assert_eq!(normalize_gateway_uri(uri), expected, "uri: {:?}", uri);
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use reqwest::Response;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Hooks called around every HTTP request made by `Gateway` and
+/// `CrossbarClient`, e.g. to export request counts, latencies and error
+/// rates to Prometheus or statsd. `method` names the client call, such as
+/// `gateway.fetch_signatures` or `crossbar.simulate_feeds`.
+pub trait HttpObserver: Send + Sync {
+    /// Called right before a request is sent
+    fn on_request(&self, _method: &str) {}
+
+    /// Called once the response headers arrive, or the request fails.
+    /// `status` is `None` if no response was received, e.g. on a timeout.
+    fn on_response(&self, _method: &str, _status: Option<u16>, _elapsed: Duration) {}
+}
+
+/// The default observer, which ignores every request
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoopHttpObserver;
+
+impl HttpObserver for NoopHttpObserver {}
+
+/// A cheaply cloneable handle to an `HttpObserver`, defaulting to `NoopHttpObserver`
+#[derive(Clone)]
+pub struct SharedHttpObserver(Arc<dyn HttpObserver>);
+
+impl SharedHttpObserver {
+    pub fn new(observer: Arc<dyn HttpObserver>) -> Self {
+        Self(observer)
+    }
+
+    /// Runs `request`, reporting it to the observer as `method`
+    pub(crate) async fn observe<F>(&self, method: &str, request: F) -> reqwest::Result<Response>
+    where
+        F: Future<Output = reqwest::Result<Response>>,
+    {
+        self.0.on_request(method);
+        let started = Instant::now();
+        let result = request.await;
+        let status = result.as_ref().ok().map(|x| x.status().as_u16());
+        self.0.on_response(method, status, started.elapsed());
+        result
+    }
+}
+
+impl Default for SharedHttpObserver {
+    fn default() -> Self {
+        Self(Arc::new(NoopHttpObserver))
+    }
+}
+
+impl std::fmt::Debug for SharedHttpObserver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SharedHttpObserver")
+    }
+}
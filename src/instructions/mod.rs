@@ -2,9 +2,24 @@ pub mod pull_feed_submit_response_ix;
 pub use pull_feed_submit_response_ix::*;
 pub mod pull_feed_submit_response_many_ix;
 pub use pull_feed_submit_response_many_ix::*;
+pub mod pull_feed_init_ix;
+pub use pull_feed_init_ix::*;
+pub mod pull_feed_set_configs_ix;
+pub use pull_feed_set_configs_ix::*;
+pub mod pull_feed_close_ix;
+pub use pull_feed_close_ix::*;
+pub mod randomness_commit_ix;
+pub use randomness_commit_ix::*;
+pub mod randomness_reveal_ix;
+pub use randomness_reveal_ix::*;
 use sha2::{Digest, Sha256};
 
 pub fn get_discriminator(name: &str) -> Vec<u8> {
     let name = format!("global:{}", name);
     Sha256::digest(&name)[..8].to_vec()
 }
+
+pub fn get_account_discriminator(name: &str) -> Vec<u8> {
+    let name = format!("account:{}", name);
+    Sha256::digest(&name)[..8].to_vec()
+}
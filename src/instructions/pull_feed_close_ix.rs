@@ -0,0 +1,110 @@
+use crate::get_discriminator;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
+
+#[derive(Clone, Debug)]
+pub struct PullFeedClose {
+    // mut
+    pub pull_feed: Pubkey,
+    // mut
+    pub reward_escrow: Pubkey,
+    // mut
+    pub lut: Pubkey,
+    pub lut_signer: Pubkey,
+    // mut, signer
+    pub payer: Pubkey,
+    pub state: Pubkey,
+    // signer
+    pub authority: Pubkey,
+    pub token_program: Pubkey,
+    pub associated_token_program: Pubkey,
+    pub system_program: Pubkey,
+    pub address_lookup_table_program: Pubkey,
+}
+
+impl PullFeedClose {
+    pub fn to_account_metas(&self, _is_signer: Option<bool>) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(self.pull_feed, false),
+            AccountMeta::new(self.reward_escrow, false),
+            AccountMeta::new(self.lut, false),
+            AccountMeta::new_readonly(self.lut_signer, false),
+            AccountMeta::new(self.payer, true),
+            AccountMeta::new_readonly(self.state, false),
+            AccountMeta::new_readonly(self.authority, true),
+            AccountMeta::new_readonly(self.token_program, false),
+            AccountMeta::new_readonly(self.associated_token_program, false),
+            AccountMeta::new_readonly(self.system_program, false),
+            AccountMeta::new_readonly(self.address_lookup_table_program, false),
+        ]
+    }
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize)]
+pub struct PullFeedCloseParams {}
+impl PullFeedCloseParams {
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        self.serialize(&mut buffer).unwrap();
+        buffer
+    }
+
+    pub fn data(&self) -> Vec<u8> {
+        let mut res = get_discriminator("pull_feed_close").to_vec();
+        res.extend_from_slice(&self.to_vec());
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn account_metas_match_program_order() {
+        let keys: Vec<Pubkey> = (0..11).map(|_| Pubkey::new_unique()).collect();
+        let accounts = PullFeedClose {
+            pull_feed: keys[0],
+            reward_escrow: keys[1],
+            lut: keys[2],
+            lut_signer: keys[3],
+            payer: keys[4],
+            state: keys[5],
+            authority: keys[6],
+            token_program: keys[7],
+            associated_token_program: keys[8],
+            system_program: keys[9],
+            address_lookup_table_program: keys[10],
+        };
+        let metas = accounts.to_account_metas(None);
+        let expected = [
+            // (is_writable, is_signer)
+            (true, false),
+            (true, false),
+            (true, false),
+            (false, false),
+            (true, true),
+            (false, false),
+            (false, true),
+            (false, false),
+            (false, false),
+            (false, false),
+            (false, false),
+        ];
+        assert_eq!(metas.len(), expected.len());
+        for (i, (meta, (is_writable, is_signer))) in metas.iter().zip(expected).enumerate() {
+            assert_eq!(meta.pubkey, keys[i], "account {}", i);
+            assert_eq!(meta.is_writable, is_writable, "account {} writable", i);
+            assert_eq!(meta.is_signer, is_signer, "account {} signer", i);
+        }
+    }
+
+    #[test]
+    fn data_is_the_bare_discriminator() {
+        assert_eq!(
+            PullFeedCloseParams {}.data(),
+            get_discriminator("pull_feed_close")
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use crate::get_discriminator;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
+
+#[derive(Clone, Debug)]
+pub struct PullFeedInit {
+    // mut, signer
+    pub pull_feed: Pubkey,
+    pub queue: Pubkey,
+    pub authority: Pubkey,
+    // mut
+    pub payer: Pubkey,
+    pub system_program: Pubkey,
+    pub program_state: Pubkey,
+    // mut
+    pub reward_escrow: Pubkey,
+    pub token_program: Pubkey,
+    pub associated_token_program: Pubkey,
+    pub wrapped_sol_mint: Pubkey,
+    pub lut_signer: Pubkey,
+    // mut
+    pub lut: Pubkey,
+    pub address_lookup_table_program: Pubkey,
+}
+
+impl PullFeedInit {
+    pub fn to_account_metas(&self, _is_signer: Option<bool>) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(self.pull_feed, true),
+            AccountMeta::new_readonly(self.queue, false),
+            AccountMeta::new_readonly(self.authority, false),
+            AccountMeta::new(self.payer, true),
+            AccountMeta::new_readonly(self.system_program, false),
+            AccountMeta::new_readonly(self.program_state, false),
+            AccountMeta::new(self.reward_escrow, false),
+            AccountMeta::new_readonly(self.token_program, false),
+            AccountMeta::new_readonly(self.associated_token_program, false),
+            AccountMeta::new_readonly(self.wrapped_sol_mint, false),
+            AccountMeta::new_readonly(self.lut_signer, false),
+            AccountMeta::new(self.lut, false),
+            AccountMeta::new_readonly(self.address_lookup_table_program, false),
+        ]
+    }
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize)]
+pub struct PullFeedInitParams {
+    pub feed_hash: [u8; 32],
+    pub max_variance: u64,
+    pub min_responses: u32,
+    pub name: [u8; 32],
+    pub recent_slot: u64,
+    pub ipfs_hash: [u8; 32],
+    pub min_sample_size: u8,
+    pub max_staleness: u32,
+}
+impl PullFeedInitParams {
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        self.serialize(&mut buffer).unwrap();
+        buffer
+    }
+
+    pub fn data(&self) -> Vec<u8> {
+        let mut res = get_discriminator("pull_feed_init").to_vec();
+        res.extend_from_slice(&self.to_vec());
+        res
+    }
+}
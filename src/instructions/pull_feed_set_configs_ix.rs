@@ -0,0 +1,47 @@
+use crate::get_discriminator;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
+
+#[derive(Clone, Debug)]
+pub struct PullFeedSetConfigs {
+    // mut
+    pub pull_feed: Pubkey,
+    // signer
+    pub authority: Pubkey,
+}
+
+impl PullFeedSetConfigs {
+    pub fn to_account_metas(&self, _is_signer: Option<bool>) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(self.pull_feed, false),
+            AccountMeta::new_readonly(self.authority, true),
+        ]
+    }
+}
+
+/// Settings left as `None` are unchanged.
+#[derive(Clone, Default, BorshSerialize, BorshDeserialize)]
+pub struct PullFeedSetConfigsParams {
+    pub feed_hash: Option<[u8; 32]>,
+    pub authority: Option<Pubkey>,
+    pub max_variance: Option<u64>,
+    pub min_responses: Option<u32>,
+    pub name: Option<[u8; 32]>,
+    pub ipfs_hash: Option<[u8; 32]>,
+    pub min_sample_size: Option<u8>,
+    pub max_staleness: Option<u32>,
+}
+impl PullFeedSetConfigsParams {
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        self.serialize(&mut buffer).unwrap();
+        buffer
+    }
+
+    pub fn data(&self) -> Vec<u8> {
+        let mut res = get_discriminator("pull_feed_set_configs").to_vec();
+        res.extend_from_slice(&self.to_vec());
+        res
+    }
+}
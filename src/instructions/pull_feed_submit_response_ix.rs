@@ -60,4 +60,10 @@ impl PullFeedSubmitResponseParams {
         res.extend_from_slice(&self.to_vec());
         res
     }
+
+    /// The length of `data()` without serializing it:
+    /// discriminator + slot + vec length prefix + 82 bytes per submission.
+    pub fn data_len(&self) -> usize {
+        8 + 8 + 4 + self.submissions.len() * 82
+    }
 }
@@ -1,5 +1,7 @@
 use crate::get_discriminator;
+use crate::PRECISION;
 use borsh::{BorshDeserialize, BorshSerialize};
+use rust_decimal::Decimal;
 use solana_sdk::instruction::AccountMeta;
 use solana_sdk::pubkey::Pubkey;
 
@@ -38,6 +40,27 @@ pub struct MultiSubmission {
     pub signature: [u8; 64],
     pub recovery_id: u8,
 }
+impl MultiSubmission {
+    /// The serialized length: values length prefix + 16 bytes per value +
+    /// signature + recovery id.
+    pub fn data_len(&self) -> usize {
+        4 + self.values.len() * 16 + 64 + 1
+    }
+
+    /// Whether the oracle has no value for the feed at `idx`
+    pub fn is_missing(&self, idx: usize) -> bool {
+        self.values.get(idx).map_or(true, |x| *x == i128::MAX)
+    }
+
+    /// The value for the feed at `idx`, or `None` if the oracle has no value for it
+    pub fn value_at(&self, idx: usize) -> Option<Decimal> {
+        if self.is_missing(idx) {
+            return None;
+        }
+        Decimal::try_from_i128_with_scale(self.values[idx], PRECISION).ok()
+    }
+}
+
 #[derive(Clone, BorshSerialize, BorshDeserialize)]
 pub struct PullFeedSubmitResponseManyParams {
     pub slot: u64,
@@ -55,4 +78,51 @@ impl PullFeedSubmitResponseManyParams {
         res.extend_from_slice(&self.to_vec());
         res
     }
+
+    /// The length of `data()` without serializing it:
+    /// discriminator + slot + vec length prefix + each submission's length.
+    pub fn data_len(&self) -> usize {
+        8 + 8 + 4 + self.submissions.iter().map(|x| x.data_len()).sum::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn submission(values: Vec<i128>) -> MultiSubmission {
+        MultiSubmission {
+            values,
+            signature: [0; 64],
+            recovery_id: 0,
+        }
+    }
+
+    #[test]
+    fn sentinel_marks_a_missing_value() {
+        let submission = submission(vec![
+            i128::MAX,
+            i128::MAX - 1,
+            i128::MIN,
+            1_500_000_000_000_000_000,
+        ]);
+        assert!(submission.is_missing(0));
+        assert_eq!(submission.value_at(0), None);
+        // Neighbours of the sentinel are reported values, though too large for a Decimal
+        assert!(!submission.is_missing(1));
+        assert_eq!(submission.value_at(1), None);
+        assert!(!submission.is_missing(2));
+        assert_eq!(submission.value_at(2), None);
+        assert!(!submission.is_missing(3));
+        assert_eq!(submission.value_at(3), Some(Decimal::new(15, 1)));
+    }
+
+    #[test]
+    fn out_of_range_index_is_missing() {
+        let submission = submission(vec![0]);
+        assert!(!submission.is_missing(0));
+        assert_eq!(submission.value_at(0), Some(Decimal::ZERO));
+        assert!(submission.is_missing(1));
+        assert_eq!(submission.value_at(1), None);
+    }
 }
@@ -0,0 +1,81 @@
+use crate::get_discriminator;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
+
+#[derive(Clone, Debug)]
+pub struct RandomnessCommit {
+    pub randomness: Pubkey,
+    pub queue: Pubkey,
+    pub oracle: Pubkey,
+    pub recent_slothashes: Pubkey,
+    pub authority: Pubkey,
+}
+
+impl RandomnessCommit {
+    pub fn to_account_metas(&self, _is_signer: Option<bool>) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(self.randomness, false),
+            AccountMeta::new_readonly(self.queue, false),
+            AccountMeta::new(self.oracle, false),
+            AccountMeta::new_readonly(self.recent_slothashes, false),
+            AccountMeta::new_readonly(self.authority, true),
+        ]
+    }
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize)]
+pub struct RandomnessCommitParams {}
+impl RandomnessCommitParams {
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        self.serialize(&mut buffer).unwrap();
+        buffer
+    }
+
+    pub fn data(&self) -> Vec<u8> {
+        let mut res = get_discriminator("randomness_commit").to_vec();
+        res.extend_from_slice(&self.to_vec());
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn account_metas_match_program_order() {
+        let keys: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        let accounts = RandomnessCommit {
+            randomness: keys[0],
+            queue: keys[1],
+            oracle: keys[2],
+            recent_slothashes: keys[3],
+            authority: keys[4],
+        };
+        let metas = accounts.to_account_metas(None);
+        let expected = [
+            // (is_writable, is_signer)
+            (true, false),
+            (false, false),
+            (true, false),
+            (false, false),
+            (false, true),
+        ];
+        assert_eq!(metas.len(), expected.len());
+        for (i, (meta, (is_writable, is_signer))) in metas.iter().zip(expected).enumerate() {
+            assert_eq!(meta.pubkey, keys[i], "account {}", i);
+            assert_eq!(meta.is_writable, is_writable, "account {} writable", i);
+            assert_eq!(meta.is_signer, is_signer, "account {} signer", i);
+        }
+    }
+
+    #[test]
+    fn data_is_the_bare_discriminator() {
+        assert_eq!(
+            RandomnessCommitParams {}.data(),
+            get_discriminator("randomness_commit")
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use crate::get_discriminator;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
+
+#[derive(Clone, Debug)]
+pub struct RandomnessReveal {
+    pub randomness: Pubkey,
+    pub oracle: Pubkey,
+    pub queue: Pubkey,
+    pub stats: Pubkey,
+    pub authority: Pubkey,
+    pub payer: Pubkey,
+    pub recent_slothashes: Pubkey,
+    pub system_program: Pubkey,
+    pub reward_escrow: Pubkey,
+    pub token_program: Pubkey,
+    pub wrapped_sol_mint: Pubkey,
+    pub program_state: Pubkey,
+}
+
+impl RandomnessReveal {
+    pub fn to_account_metas(&self, _is_signer: Option<bool>) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(self.randomness, false),
+            AccountMeta::new_readonly(self.oracle, false),
+            AccountMeta::new_readonly(self.queue, false),
+            AccountMeta::new(self.stats, false),
+            AccountMeta::new_readonly(self.authority, true),
+            AccountMeta::new(self.payer, true),
+            AccountMeta::new_readonly(self.recent_slothashes, false),
+            AccountMeta::new_readonly(self.system_program, false),
+            AccountMeta::new(self.reward_escrow, false),
+            AccountMeta::new_readonly(self.token_program, false),
+            AccountMeta::new_readonly(self.wrapped_sol_mint, false),
+            AccountMeta::new_readonly(self.program_state, false),
+        ]
+    }
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize)]
+pub struct RandomnessRevealParams {
+    pub signature: [u8; 64],
+    pub recovery_id: u8,
+    pub value: [u8; 32],
+}
+impl RandomnessRevealParams {
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        self.serialize(&mut buffer).unwrap();
+        buffer
+    }
+
+    pub fn data(&self) -> Vec<u8> {
+        let mut res = get_discriminator("randomness_reveal").to_vec();
+        res.extend_from_slice(&self.to_vec());
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn account_metas_match_program_order() {
+        let keys: Vec<Pubkey> = (0..12).map(|_| Pubkey::new_unique()).collect();
+        let accounts = RandomnessReveal {
+            randomness: keys[0],
+            oracle: keys[1],
+            queue: keys[2],
+            stats: keys[3],
+            authority: keys[4],
+            payer: keys[5],
+            recent_slothashes: keys[6],
+            system_program: keys[7],
+            reward_escrow: keys[8],
+            token_program: keys[9],
+            wrapped_sol_mint: keys[10],
+            program_state: keys[11],
+        };
+        let metas = accounts.to_account_metas(None);
+        let expected = [
+            // (is_writable, is_signer)
+            (true, false),
+            (false, false),
+            (false, false),
+            (true, false),
+            (false, true),
+            (true, true),
+            (false, false),
+            (false, false),
+            (true, false),
+            (false, false),
+            (false, false),
+            (false, false),
+        ];
+        assert_eq!(metas.len(), expected.len());
+        for (i, (meta, (is_writable, is_signer))) in metas.iter().zip(expected).enumerate() {
+            assert_eq!(meta.pubkey, keys[i], "account {}", i);
+            assert_eq!(meta.is_writable, is_writable, "account {} writable", i);
+            assert_eq!(meta.is_signer, is_signer, "account {} signer", i);
+        }
+    }
+
+    #[test]
+    fn data_is_the_discriminator_and_borsh_params() {
+        let data = RandomnessRevealParams {
+            signature: [1; 64],
+            recovery_id: 2,
+            value: [3; 32],
+        }
+        .data();
+        assert_eq!(data.len(), 8 + 64 + 1 + 32);
+        assert_eq!(data[..8], get_discriminator("randomness_reveal"));
+        assert_eq!(data[8..72], [1; 64]);
+        assert_eq!(data[72], 2);
+        assert_eq!(data[73..], [3; 32]);
+    }
+}
@@ -4,10 +4,16 @@ pub mod crossbar;
 pub use crossbar::*;
 pub mod gateway;
 pub use gateway::*;
+pub mod http_observer;
+pub use http_observer::*;
+pub mod rate_limiter;
+pub use rate_limiter::*;
 pub mod pull_feed;
 pub use pull_feed::*;
 pub mod associated_token_account;
 pub mod oracle_job;
+pub mod oracle_job_builder;
+pub use oracle_job_builder::*;
 pub use associated_token_account::*;
 pub mod recent_slothashes;
 pub use recent_slothashes::*;
@@ -18,17 +24,33 @@ pub mod lut;
 #[cfg(feature = "solana_sdk_1_16")]
 pub use lut::*;
 pub mod lut_owner;
+pub mod account_fetcher;
+pub use account_fetcher::*;
+pub mod pda;
+pub use pda::*;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod lookup_table;
+pub use lookup_table::*;
+pub mod serde_helpers;
 use crate::oracle_job::OracleJob;
+use anyhow_ext::anyhow;
 use anyhow_ext::Error as AnyhowError;
 use lazy_static::lazy_static;
 pub use lut_owner::*;
 use solana_sdk::hash;
 use solana_sdk::instruction::Instruction;
 use solana_sdk::message::Message;
+use solana_sdk::message::{v0, VersionedMessage};
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Signer;
 use solana_sdk::signer::keypair::Keypair;
 use solana_sdk::transaction::Transaction;
+use solana_sdk::transaction::VersionedTransaction;
+#[cfg(feature = "solana_sdk_1_16")]
+use solana_sdk::address_lookup_table_account::AddressLookupTableAccount;
+#[cfg(not(feature = "solana_sdk_1_16"))]
+use solana_sdk::address_lookup_table::AddressLookupTableAccount;
 use std::str::FromStr;
 
 lazy_static! {
@@ -52,6 +74,8 @@ pub const LUT_SIGNER_SEED: &[u8] = b"LutSigner";
 pub const DELEGATION_SEED: &[u8] = b"Delegation";
 pub const DELEGATION_GROUP_SEED: &[u8] = b"Group";
 pub const REWARD_POOL_VAULT_SEED: &[u8] = b"RewardPool";
+/// The maximum number of accounts a single getMultipleAccounts request may fetch.
+pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
 
 pub fn ix_to_tx(
     ixs: &[Instruction],
@@ -63,3 +87,21 @@ pub fn ix_to_tx(
     tx.try_sign(&signers.to_vec(), blockhash)?;
     Ok(tx)
 }
+
+/// Compiles the instructions into a signed v0 transaction resolving accounts
+/// through `luts`, e.g. the lookup tables returned by `PullFeed::fetch_update_ix`.
+/// The first signer pays for the transaction.
+pub fn ix_to_versioned_tx(
+    ixs: &[Instruction],
+    signers: &[&Keypair],
+    luts: &[AddressLookupTableAccount],
+    blockhash: hash::Hash,
+) -> Result<VersionedTransaction, AnyhowError> {
+    let payer = signers
+        .first()
+        .ok_or_else(|| anyhow!("ix_to_versioned_tx: No signers provided"))?
+        .pubkey();
+    let msg = v0::Message::try_compile(&payer, ixs, luts, blockhash)?;
+    let tx = VersionedTransaction::try_new(VersionedMessage::V0(msg), &signers.to_vec())?;
+    Ok(tx)
+}
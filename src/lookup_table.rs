@@ -0,0 +1,141 @@
+use crate::AccountFetcher;
+use crate::ADDRESS_LOOKUP_TABLE_PROGRAM_ID;
+#[cfg(feature = "solana_sdk_1_16")]
+use crate::{derive_lookup_table_address, LookupTableMeta, LookupTableStatus};
+use anyhow_ext::anyhow;
+use anyhow_ext::Context;
+use anyhow_ext::Error as AnyhowError;
+use serde::Serialize;
+#[cfg(not(feature = "solana_sdk_1_16"))]
+use solana_sdk::address_lookup_table::instruction::derive_lookup_table_address;
+#[cfg(not(feature = "solana_sdk_1_16"))]
+use solana_sdk::address_lookup_table::state::{LookupTableMeta, LookupTableStatus};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::slot_hashes::SlotHashes;
+use solana_sdk::system_program;
+
+/// The Address Lookup Table program's instructions. These are bincode
+/// encoded, so the variant order must match the program's.
+#[derive(Serialize)]
+enum ProgramInstruction {
+    CreateLookupTable {
+        recent_slot: u64,
+        bump_seed: u8,
+    },
+    // Not built by this crate; kept so the variant indices line up.
+    #[allow(dead_code)]
+    FreezeLookupTable,
+    ExtendLookupTable {
+        new_addresses: Vec<Pubkey>,
+    },
+    DeactivateLookupTable,
+    CloseLookupTable,
+}
+
+fn lookup_table_ix(data: ProgramInstruction, accounts: Vec<AccountMeta>) -> Instruction {
+    Instruction {
+        program_id: *ADDRESS_LOOKUP_TABLE_PROGRAM_ID,
+        data: bincode::serialize(&data).unwrap(),
+        accounts,
+    }
+}
+
+/// Builds an instruction creating a lookup table owned by `authority` at the
+/// address derived from `authority` and `recent_slot`. The authority does not
+/// need to sign, so it may be a PDA such as a `find_lut_signer` key.
+/// # Returns
+/// * `(Instruction, Pubkey)` - The instruction and the new lookup table's address
+pub fn create_lookup_table(
+    authority: Pubkey,
+    payer: Pubkey,
+    recent_slot: u64,
+) -> (Instruction, Pubkey) {
+    let (lut, bump_seed) = derive_lookup_table_address(&authority, recent_slot);
+    let ix = lookup_table_ix(
+        ProgramInstruction::CreateLookupTable {
+            recent_slot,
+            bump_seed,
+        },
+        vec![
+            AccountMeta::new(lut, false),
+            AccountMeta::new_readonly(authority, false),
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    );
+    (ix, lut)
+}
+
+/// Builds an instruction appending `addresses` to `lut`. `payer` funds the
+/// extra rent, if any.
+pub fn extend_lookup_table(
+    lut: Pubkey,
+    authority: Pubkey,
+    payer: Option<Pubkey>,
+    addresses: Vec<Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(lut, false),
+        AccountMeta::new_readonly(authority, true),
+    ];
+    if let Some(payer) = payer {
+        accounts.push(AccountMeta::new(payer, true));
+        accounts.push(AccountMeta::new_readonly(system_program::ID, false));
+    }
+    lookup_table_ix(
+        ProgramInstruction::ExtendLookupTable {
+            new_addresses: addresses,
+        },
+        accounts,
+    )
+}
+
+/// Builds an instruction deactivating `lut`. A deactivated table can no longer
+/// be extended, and can only be closed once its deactivation slot has left the
+/// slot hashes sysvar, roughly 512 slots later. Until then its
+/// `LookupTableMeta::status` reports `Deactivating` and lookups still resolve.
+pub fn deactivate_lookup_table(lut: Pubkey, authority: Pubkey) -> Instruction {
+    lookup_table_ix(
+        ProgramInstruction::DeactivateLookupTable,
+        vec![
+            AccountMeta::new(lut, false),
+            AccountMeta::new_readonly(authority, true),
+        ],
+    )
+}
+
+/// Builds an instruction closing a deactivated `lut` and sending its rent to
+/// `recipient`. Fails on-chain unless `LookupTableMeta::status` is `Deactivated`.
+pub fn close_lookup_table(lut: Pubkey, authority: Pubkey, recipient: Pubkey) -> Instruction {
+    lookup_table_ix(
+        ProgramInstruction::CloseLookupTable,
+        vec![
+            AccountMeta::new(lut, false),
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(recipient, false),
+        ],
+    )
+}
+
+/// Reports a lookup table's `LookupTableStatus` against live chain state,
+/// reading the slot hashes sysvar and the current slot in a single RPC.
+/// A table may only be closed once this returns `Deactivated`.
+pub async fn lookup_table_status(
+    client: &impl AccountFetcher,
+    lut_meta: &LookupTableMeta,
+) -> Result<LookupTableStatus, AnyhowError> {
+    let (current_slot, account) = client
+        .get_account_with_slot(
+            &solana_sdk::sysvar::slot_hashes::ID,
+            CommitmentConfig::confirmed(),
+        )
+        .await
+        .context("lookup_table_status: Failed to fetch slot hashes")?;
+    let account =
+        account.ok_or_else(|| anyhow!("lookup_table_status: Slot hashes sysvar not found"))?;
+    let slot_hashes: SlotHashes = bincode::deserialize(&account.data)
+        .map_err(|_| anyhow!("lookup_table_status: Invalid slot hashes data"))?;
+    Ok(lut_meta.status(current_slot, &slot_hashes))
+}
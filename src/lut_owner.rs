@@ -4,7 +4,7 @@ use crate::LUT_SIGNER_SEED;
 use crate::SWITCHBOARD_ON_DEMAND_PROGRAM_ID;
 use anyhow_ext::anyhow;
 use anyhow_ext::Error as AnyhowError;
-use solana_client::nonblocking::rpc_client::RpcClient;
+use lazy_static::lazy_static;
 use solana_sdk::account::Account;
 #[cfg(not(feature = "solana_sdk_1_16"))]
 use solana_sdk::address_lookup_table::instruction::derive_lookup_table_address;
@@ -15,32 +15,51 @@ use solana_sdk::address_lookup_table::AddressLookupTableAccount;
 #[cfg(feature = "solana_sdk_1_16")]
 use solana_sdk::address_lookup_table_account::AddressLookupTableAccount;
 use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+lazy_static! {
+    pub static ref ADDRESS_LOOKUP_TABLE_PROGRAM_ID: Pubkey =
+        Pubkey::from_str("AddressLookupTab1e1111111111111111111111111").unwrap();
+}
 
 pub fn find_lut_signer(k: &Pubkey) -> Pubkey {
-    Pubkey::find_program_address(
-        &[LUT_SIGNER_SEED, k.as_ref()],
-        &SWITCHBOARD_ON_DEMAND_PROGRAM_ID,
-    )
-    .0
+    find_lut_signer_with_program_id(k, &SWITCHBOARD_ON_DEMAND_PROGRAM_ID)
+}
+
+/// The lookup table signer of `k` under a custom program ID, e.g. a localnet deployment
+pub fn find_lut_signer_with_program_id(k: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[LUT_SIGNER_SEED, k.as_ref()], program_id).0
 }
 
 pub trait LutOwner {
+    /// The Anchor account name, checked against the account discriminator
+    const ACCOUNT_NAME: &'static str;
+
     fn lut_slot(&self) -> u64;
 }
 
 pub async fn load_lookup_table<T: LutOwner + bytemuck::Pod>(
-    client: &RpcClient,
+    client: &impl AccountFetcher,
     self_key: Pubkey,
+) -> Result<AddressLookupTableAccount, AnyhowError> {
+    load_lookup_table_with_program_id::<T>(client, self_key, &SWITCHBOARD_ON_DEMAND_PROGRAM_ID)
+        .await
+}
+
+/// Like `load_lookup_table`, for accounts owned by a custom program ID
+pub async fn load_lookup_table_with_program_id<T: LutOwner + bytemuck::Pod>(
+    client: &impl AccountFetcher,
+    self_key: Pubkey,
+    program_id: &Pubkey,
 ) -> Result<AddressLookupTableAccount, AnyhowError> {
     let account = client
         .get_account_data(&self_key)
         .await
         .map_err(|_| anyhow!("LutOwner.load_lookup_table: Oracle not found"))?;
-    let account = account[8..].to_vec();
-    let data = bytemuck::try_from_bytes::<T>(&account)
-        .map_err(|_| anyhow!("LutOwner.load_lookup_table: Invalid data"))?;
+    let data = parse_account::<T>(&account, T::ACCOUNT_NAME)
+        .map_err(|e| anyhow!("LutOwner.load_lookup_table: {}", e))?;
     let lut_slot = data.lut_slot();
-    let lut_signer = find_lut_signer(&self_key);
+    let lut_signer = find_lut_signer_with_program_id(&self_key, program_id);
     let lut_key = derive_lookup_table_address(&lut_signer, lut_slot).0;
     let lut_account = client
         .get_account_data(&lut_key)
@@ -54,16 +73,48 @@ pub async fn load_lookup_table<T: LutOwner + bytemuck::Pod>(
     })
 }
 
+/// Decodes raw lookup table account data into an `AddressLookupTableAccount`.
+/// Lookup table accounts carry no anchor discriminator, so `data` must be the
+/// full account data starting at offset 0.
+pub fn decode_lookup_table(
+    key: Pubkey,
+    data: &[u8],
+) -> Result<AddressLookupTableAccount, AnyhowError> {
+    let parsed_lut = AddressLookupTable::deserialize(data)
+        .map_err(|_| anyhow!("LutOwner.decode_lookup_table: Invalid LUT data"))?;
+    Ok(AddressLookupTableAccount {
+        addresses: parsed_lut.addresses.to_vec(),
+        key,
+    })
+}
+
+/// Flattens lookup table accounts into `(lut key, addresses)` pairs, for
+/// custom message compilers or for serializing LUT contents.
+pub fn lookup_table_addresses(luts: &[AddressLookupTableAccount]) -> Vec<(Pubkey, Vec<Pubkey>)> {
+    luts.iter()
+        .map(|lut| (lut.key, lut.addresses.clone()))
+        .collect()
+}
+
 fn account_to_vec(account: Option<Account>) -> Vec<u8> {
     match account {
-        Some(account) => account.data.get(8..).unwrap_or(&[]).to_vec(),
+        Some(account) => account.data,
         None => vec![],
     }
 }
 
 pub async fn load_lookup_tables<T: LutOwner + bytemuck::Pod>(
-    client: &RpcClient,
+    client: &impl AccountFetcher,
+    keys: &[Pubkey],
+) -> Result<Vec<AddressLookupTableAccount>, AnyhowError> {
+    load_lookup_tables_with_program_id::<T>(client, keys, &SWITCHBOARD_ON_DEMAND_PROGRAM_ID).await
+}
+
+/// Like `load_lookup_tables`, for accounts owned by a custom program ID
+pub async fn load_lookup_tables_with_program_id<T: LutOwner + bytemuck::Pod>(
+    client: &impl AccountFetcher,
     keys: &[Pubkey],
+    program_id: &Pubkey,
 ) -> Result<Vec<AddressLookupTableAccount>, AnyhowError> {
     let accounts_data = client
         .get_multiple_accounts(&keys)
@@ -74,10 +125,10 @@ pub async fn load_lookup_tables<T: LutOwner + bytemuck::Pod>(
     let mut lut_keys = Vec::new();
     let mut out = Vec::new();
     for (idx, account) in accounts_data.iter().enumerate() {
-        let data = bytemuck::try_from_bytes::<T>(&account)
-            .map_err(|_| anyhow!("LutOwner.load_lookup_tables: Invalid data"))?;
+        let data = parse_account::<T>(account, T::ACCOUNT_NAME)
+            .map_err(|e| anyhow!("LutOwner.load_lookup_tables: {}: {}", keys[idx], e))?;
         let lut_slot = data.lut_slot();
-        let lut_signer = find_lut_signer(&keys[idx]);
+        let lut_signer = find_lut_signer_with_program_id(&keys[idx], program_id);
         let lut_key = derive_lookup_table_address(&lut_signer, lut_slot).0;
         lut_keys.push(lut_key);
     }
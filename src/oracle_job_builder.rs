@@ -0,0 +1,100 @@
+use crate::oracle_job::oracle_job::http_task::{Header, Method};
+use crate::oracle_job::oracle_job::task;
+use crate::oracle_job::oracle_job::{HttpTask, JsonParseTask, MedianTask, Task};
+use crate::oracle_job::OracleJob;
+use crate::try_encode_jobs;
+use anyhow_ext::Error as AnyhowError;
+
+/// Builds an `OracleJob` as a chain of tasks, e.g. an HTTP request followed by
+/// a JSON parse.
+#[derive(Clone, Debug, Default)]
+pub struct OracleJobBuilder {
+    tasks: Vec<Task>,
+}
+
+impl OracleJobBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends an arbitrary task
+    pub fn task(mut self, task: task::Task) -> Self {
+        self.tasks.push(Task { task: Some(task) });
+        self
+    }
+
+    /// Appends an HTTP GET of `url`
+    pub fn http_get(self, url: &str) -> Self {
+        self.http(url, Method::Get, &[], None)
+    }
+
+    /// Appends an HTTP request
+    pub fn http(
+        self,
+        url: &str,
+        method: Method,
+        headers: &[(&str, &str)],
+        body: Option<&str>,
+    ) -> Self {
+        self.task(task::Task::HttpTask(HttpTask {
+            url: Some(url.to_string()),
+            method: Some(method as i32),
+            headers: headers
+                .iter()
+                .map(|(key, value)| Header {
+                    key: Some(key.to_string()),
+                    value: Some(value.to_string()),
+                })
+                .collect(),
+            body: body.map(|x| x.to_string()),
+        }))
+    }
+
+    /// Appends a JSON parse of the previous task's result at the JSONPath `path`
+    pub fn json_parse(self, path: &str) -> Self {
+        self.task(task::Task::JsonParseTask(JsonParseTask {
+            path: Some(path.to_string()),
+            aggregation_method: None,
+        }))
+    }
+
+    /// Appends the median of the results of `jobs`
+    /// # Arguments
+    /// * `jobs` - The jobs to run
+    /// * `min_successful_required` - The minimum number of jobs that must succeed
+    pub fn median(self, jobs: Vec<OracleJob>, min_successful_required: Option<i32>) -> Self {
+        self.task(task::Task::MedianTask(MedianTask {
+            tasks: Vec::new(),
+            jobs,
+            min_successful_required,
+            max_range_percent: None,
+        }))
+    }
+
+    /// Builds the job, checking that it encodes cleanly
+    pub fn build(self) -> Result<OracleJob, AnyhowError> {
+        let job = OracleJob { tasks: self.tasks };
+        try_encode_jobs(vec![job.clone()])?;
+        Ok(job)
+    }
+}
+
+/// A job fetching `url` and parsing the value at the JSONPath `path`
+pub fn http_json_job(url: &str, path: &str) -> Result<OracleJob, AnyhowError> {
+    OracleJobBuilder::new().http_get(url).json_parse(path).build()
+}
+
+/// A job taking the median of several `http_json_job`s, one per `(url, path)`
+/// source, requiring at least `min_successful_required` of them to succeed.
+pub fn median_http_json_job(
+    sources: &[(&str, &str)],
+    min_successful_required: Option<i32>,
+) -> Result<OracleJob, AnyhowError> {
+    let jobs = sources
+        .iter()
+        .map(|(url, path)| http_json_job(url, path))
+        .collect::<Result<Vec<_>, _>>()?;
+    OracleJobBuilder::new()
+        .median(jobs, min_successful_required)
+        .build()
+}
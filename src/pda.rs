@@ -0,0 +1,33 @@
+use crate::*;
+use solana_sdk::pubkey::Pubkey;
+
+pub fn find_delegation(staker: &Pubkey, queue: &Pubkey) -> Pubkey {
+    find_delegation_with_program_id(staker, queue, &SWITCHBOARD_ON_DEMAND_PROGRAM_ID)
+}
+
+/// The delegation of `staker` to `queue` under a custom program ID, e.g. a localnet deployment
+pub fn find_delegation_with_program_id(
+    staker: &Pubkey,
+    queue: &Pubkey,
+    program_id: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(&[DELEGATION_SEED, queue.as_ref(), staker.as_ref()], program_id).0
+}
+
+pub fn find_delegation_group(queue: &Pubkey) -> Pubkey {
+    find_delegation_group_with_program_id(queue, &SWITCHBOARD_ON_DEMAND_PROGRAM_ID)
+}
+
+/// The delegation group of `queue` under a custom program ID, e.g. a localnet deployment
+pub fn find_delegation_group_with_program_id(queue: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[DELEGATION_GROUP_SEED, queue.as_ref()], program_id).0
+}
+
+pub fn find_reward_pool_vault(queue: &Pubkey) -> Pubkey {
+    find_reward_pool_vault_with_program_id(queue, &SWITCHBOARD_ON_DEMAND_PROGRAM_ID)
+}
+
+/// The reward pool vault of `queue` under a custom program ID, e.g. a localnet deployment
+pub fn find_reward_pool_vault_with_program_id(queue: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[REWARD_POOL_VAULT_SEED, queue.as_ref()], program_id).0
+}
@@ -2,26 +2,43 @@ use crate::Gateway;
 use crate::OracleAccountData;
 use crate::State;
 use crate::*;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::sync::OnceCell;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+use tokio::sync::{OnceCell, Semaphore, SemaphorePermit};
 use anyhow_ext::anyhow;
 use anyhow_ext::Context;
 use dashmap::DashMap;
 use anyhow_ext::Error as AnyhowError;
 use associated_token_account::get_associated_token_address;
+use associated_token_account::get_associated_token_address_with_program_id;
 use associated_token_account::NATIVE_MINT;
+use associated_token_account::SPL_ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID;
 use associated_token_account::SPL_TOKEN_PROGRAM_ID;
 use base64::{engine::general_purpose::STANDARD as base64, Engine as _};
-use bs58;
 use bytemuck;
-use futures::future::try_join_all;
+use futures::future::{self, try_join_all};
+use futures::stream::{self, Stream, StreamExt};
 use tokio::join;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_account_decoder::{UiAccount, UiAccountEncoding, UiDataSliceConfig};
+use solana_client::nonblocking::pubsub_client::{PubsubClient, UnsubscribeFn};
+use solana_client::rpc_config::{
+    RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSendTransactionConfig,
+};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::genesis_config::ClusterType;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
 use solana_sdk::instruction::AccountMeta;
 use solana_sdk::instruction::Instruction;
+use solana_sdk::packet::PACKET_DATA_SIZE;
 use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature};
 use solana_sdk::system_program;
 use std::future::Future;
 use std::pin::Pin;
@@ -30,28 +47,323 @@ use std::result::Result;
 use solana_sdk::address_lookup_table_account::AddressLookupTableAccount;
 #[cfg(not(feature = "solana_sdk_1_16"))]
 use solana_sdk::address_lookup_table::AddressLookupTableAccount;
+#[cfg(not(feature = "solana_sdk_1_16"))]
+use solana_sdk::address_lookup_table::instruction::derive_lookup_table_address;
+
+/// Compute units reserved for an update regardless of its number of submissions
+pub const SUBMIT_BASE_COMPUTE_UNITS: u32 = 100_000;
+/// Compute units consumed per oracle submission (signature recovery and stats update)
+pub const SUBMIT_COMPUTE_UNITS_PER_SUBMISSION: u32 = 35_000;
+/// The maximum compute units a transaction may request
+pub const MAX_COMPUTE_UNITS: u32 = 1_400_000;
+/// The default percentile of recent prioritization fees used for the compute unit price
+pub const DEFAULT_PRIORITY_FEE_PERCENTILE: u8 = 75;
 
 type LutCache = DashMap<Pubkey, AddressLookupTableAccount>;
-type JobCache = DashMap<[u8; 32], OnceCell<Vec<OracleJob>>>;
-type PullFeedCache = DashMap<Pubkey, OnceCell<PullFeedAccountData>>;
+type JobCache = DashMap<[u8; 32], Arc<OnceCell<Vec<OracleJob>>>>;
+type PullFeedCache = DashMap<Pubkey, Arc<OnceCell<PullFeedAccountData>>>;
+type QueueCache = DashMap<Pubkey, Arc<OnceCell<QueueAccountData>>>;
+
+/// Tracks when cache entries were last used so a bounded cache can evict
+/// its least recently used entry.
+#[derive(Default)]
+struct Recency<K: Eq + Hash> {
+    clock: AtomicU64,
+    last_used: DashMap<K, u64>,
+}
+
+impl<K: Eq + Hash + Clone> Recency<K> {
+    fn touch(&self, key: &K) {
+        let now = self.clock.fetch_add(1, Ordering::Relaxed);
+        self.last_used.insert(key.clone(), now);
+    }
+
+    /// Evicts least recently used entries from `map` until there is room for
+    /// one more below `capacity`, returning the evicted keys.
+    fn make_room<V>(&self, map: &DashMap<K, V>, capacity: usize) -> Vec<K> {
+        let mut evicted = Vec::new();
+        while map.len() >= capacity {
+            let lru = self
+                .last_used
+                .iter()
+                .min_by_key(|x| *x.value())
+                .map(|x| x.key().clone());
+            match lru {
+                Some(key) => {
+                    self.last_used.remove(&key);
+                    map.remove(&key);
+                    evicted.push(key);
+                }
+                None => break,
+            }
+        }
+        evicted
+    }
+}
+
+/// Hit and miss counters for one cache
+#[derive(Default)]
+struct CacheCounter {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl CacheCounter {
+    fn record(&self, hit: bool) {
+        let counter = if hit { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> CacheHitMiss {
+        CacheHitMiss {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// The number of lookups a cache served from memory (`hits`) and from the
+/// network (`misses`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheHitMiss {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// A snapshot of `SbContext`'s cache counters, see `SbContext::stats`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub lut: CacheHitMiss,
+    pub job: CacheHitMiss,
+    pub pull_feed: CacheHitMiss,
+    pub queue: CacheHitMiss,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SbContextConfig {
+    /// The maximum number of entries in each of the lut, job, pull feed and
+    /// queue caches, evicting the least recently used entry when full. Unbounded if `None`.
+    pub max_entries: Option<usize>,
+    /// How long loaded pull feed data is reused before it is re-fetched, so
+    /// configuration changes are picked up. Cached forever if `None`.
+    pub pull_feed_ttl: Option<Duration>,
+    /// The on-demand program to build instructions and derive addresses for,
+    /// e.g. a localnet deployment. Defaults to `SWITCHBOARD_ON_DEMAND_PROGRAM_ID`.
+    pub program_id: Option<Pubkey>,
+    /// The most account and lookup table fetches the context runs at once,
+    /// to smooth RPC load when updating many feeds. Unlimited if `None`.
+    pub max_concurrent_fetches: Option<usize>,
+}
 
 pub struct SbContext {
     pub lut_cache: LutCache,
     pub job_cache: JobCache,
     pub pull_feed_cache: PullFeedCache,
+    pub queue_cache: QueueCache,
+    /// The most recently fetched slothash and when it was fetched
+    pub slothash_cache: Mutex<Option<(Instant, SlotHash)>>,
+    max_entries: Option<usize>,
+    pull_feed_ttl: Option<Duration>,
+    program_id: Pubkey,
+    pull_feed_loaded_at: DashMap<Pubkey, Instant>,
+    token_programs: DashMap<Pubkey, Pubkey>,
+    fetch_limit: Option<Semaphore>,
+    lut_recency: Recency<Pubkey>,
+    job_recency: Recency<[u8; 32]>,
+    pull_feed_recency: Recency<Pubkey>,
+    queue_recency: Recency<Pubkey>,
+    lut_counter: CacheCounter,
+    job_counter: CacheCounter,
+    pull_feed_counter: CacheCounter,
+    queue_counter: CacheCounter,
 }
 impl SbContext {
+    /// A context whose caches grow without bound
     pub fn new() -> Arc<Self> {
+        Self::with_config(SbContextConfig::default())
+    }
+
+    /// A context whose `lut_cache`, `job_cache`, `pull_feed_cache` and
+    /// `queue_cache` each hold at most `max_entries` entries. When a cache is full, the entry
+    /// used least recently (read or inserted) is evicted to make room.
+    pub fn with_capacity(max_entries: usize) -> Arc<Self> {
+        Self::with_config(SbContextConfig {
+            max_entries: Some(max_entries),
+            ..Default::default()
+        })
+    }
+
+    pub fn with_config(config: SbContextConfig) -> Arc<Self> {
         Arc::new(SbContext {
             lut_cache: DashMap::new(),
             job_cache: DashMap::new(),
             pull_feed_cache: DashMap::new(),
+            queue_cache: DashMap::new(),
+            slothash_cache: Mutex::new(None),
+            max_entries: config.max_entries.map(|x| x.max(1)),
+            pull_feed_ttl: config.pull_feed_ttl,
+            program_id: config
+                .program_id
+                .unwrap_or(*SWITCHBOARD_ON_DEMAND_PROGRAM_ID),
+            pull_feed_loaded_at: DashMap::new(),
+            token_programs: DashMap::new(),
+            fetch_limit: config
+                .max_concurrent_fetches
+                .map(|x| Semaphore::new(x.max(1))),
+            lut_recency: Recency::default(),
+            job_recency: Recency::default(),
+            pull_feed_recency: Recency::default(),
+            queue_recency: Recency::default(),
+            lut_counter: CacheCounter::default(),
+            job_counter: CacheCounter::default(),
+            pull_feed_counter: CacheCounter::default(),
+            queue_counter: CacheCounter::default(),
         })
     }
+
+    /// Waits for a free fetch slot if `max_concurrent_fetches` is set. The
+    /// slot is held until the returned permit is dropped. Never await it while
+    /// holding a cache map guard: other tasks touching that shard would block
+    /// their thread until a permit frees up, deadlocking when permits are
+    /// scarce. Clone the cache cell out first, see `queue_cell`.
+    async fn fetch_permit(&self) -> Option<SemaphorePermit<'_>> {
+        match &self.fetch_limit {
+            // The semaphore is never closed
+            Some(limit) => limit.acquire().await.ok(),
+            None => None,
+        }
+    }
+
+    /// The on-demand program this context builds instructions for
+    pub fn program_id(&self) -> Pubkey {
+        self.program_id
+    }
+
+    /// The hits and misses of each cache since the context was created
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            lut: self.lut_counter.snapshot(),
+            job: self.job_counter.snapshot(),
+            pull_feed: self.pull_feed_counter.snapshot(),
+            queue: self.queue_counter.snapshot(),
+        }
+    }
+
+    /// Evicts the cached account data for `feed`, e.g. after its authority
+    /// reconfigures it. The next update re-fetches it.
+    pub fn invalidate_feed(&self, feed: &Pubkey) {
+        self.pull_feed_cache.remove(feed);
+        self.pull_feed_loaded_at.remove(feed);
+        self.pull_feed_recency.last_used.remove(feed);
+    }
+
+    /// Evicts the cached jobs for `feed_hash`
+    pub fn invalidate_jobs(&self, feed_hash: &[u8; 32]) {
+        self.job_cache.remove(feed_hash);
+        self.job_recency.last_used.remove(feed_hash);
+    }
+
+    /// Evicts the cached account data for `queue`
+    pub fn invalidate_queue(&self, queue: &Pubkey) {
+        self.queue_cache.remove(queue);
+        self.queue_recency.last_used.remove(queue);
+    }
+
+    /// Loads a queue through the `queue_cache`. A single queue backs many
+    /// feeds, so it is fetched once and reused until invalidated.
+    pub async fn load_queue(
+        &self,
+        client: &impl AccountFetcher,
+        queue: &Pubkey,
+    ) -> Result<QueueAccountData, AnyhowError> {
+        let cell = self.queue_cell(queue);
+        self.queue_counter.record(cell.initialized());
+        let data = *cell
+            .get_or_try_init(|| async {
+                let _permit = self.fetch_permit().await;
+                QueueAccountData::load(client, queue).await
+            })
+            .await?;
+        Ok(data)
+    }
+
+    /// The queue cache cell for `queue`, created if missing. The cell is cloned
+    /// out of the map so the shard lock is released before awaiting it.
+    fn queue_cell(&self, queue: &Pubkey) -> Arc<OnceCell<QueueAccountData>> {
+        if !self.queue_cache.contains_key(queue) {
+            if let Some(max_entries) = self.max_entries {
+                self.queue_recency.make_room(&self.queue_cache, max_entries);
+            }
+        }
+        self.queue_recency.touch(queue);
+        self.queue_cache
+            .entry(*queue)
+            .or_insert_with(|| Arc::new(OnceCell::new()))
+            .value()
+            .clone()
+    }
+
+    /// The token program owning `mint`, either the classic token program or
+    /// Token-2022. A mint never changes owner, so the result is cached.
+    pub async fn load_token_program(
+        &self,
+        client: &impl AccountFetcher,
+        mint: &Pubkey,
+    ) -> Result<Pubkey, AnyhowError> {
+        if *mint == *NATIVE_MINT {
+            return Ok(*SPL_TOKEN_PROGRAM_ID);
+        }
+        if let Some(program) = self.token_programs.get(mint) {
+            return Ok(*program);
+        }
+        let account = client
+            .get_account_with_commitment(mint, client.commitment())
+            .await
+            .ok()
+            .flatten()
+            .ok_or_else(|| anyhow!("SbContext.load_token_program: Mint {} not found", mint))?;
+        self.token_programs.insert(*mint, account.owner);
+        Ok(account.owner)
+    }
+
+    /// Empties every cache
+    pub fn clear(&self) {
+        self.lut_cache.clear();
+        self.job_cache.clear();
+        self.pull_feed_cache.clear();
+        self.pull_feed_loaded_at.clear();
+        self.queue_cache.clear();
+        self.token_programs.clear();
+        self.lut_recency.last_used.clear();
+        self.job_recency.last_used.clear();
+        self.pull_feed_recency.last_used.clear();
+        self.queue_recency.last_used.clear();
+        *self.slothash_cache.lock().unwrap() = None;
+    }
+}
+
+/// The mint `queue` pays oracle rewards in and the token program owning it,
+/// unless overridden by `reward_mint` and `token_program`
+async fn fetch_reward_mint(
+    context: &SbContext,
+    client: &impl AccountFetcher,
+    queue: &Pubkey,
+    reward_mint: Option<Pubkey>,
+    token_program: Option<Pubkey>,
+) -> Result<(Pubkey, Pubkey), AnyhowError> {
+    let reward_mint = match reward_mint {
+        Some(mint) => mint,
+        None => context.load_queue(client, queue).await?.reward_mint(),
+    };
+    let token_program = match token_program {
+        Some(program) => program,
+        None => context.load_token_program(client, &reward_mint).await?,
+    };
+    Ok((reward_mint, token_program))
 }
 
 async fn fetch_and_cache_luts<T: bytemuck::Pod + lut_owner::LutOwner>(
-    client: &RpcClient,
+    client: &impl AccountFetcher,
     context: Arc<SbContext>,
     oracle_keys: &[Pubkey],
 ) -> Result<Vec<AddressLookupTableAccount>, AnyhowError> {
@@ -61,15 +373,26 @@ async fn fetch_and_cache_luts<T: bytemuck::Pod + lut_owner::LutOwner>(
     for &key in oracle_keys {
         if let Some(cached_lut) = context.lut_cache.get(&key) {
             luts.push(cached_lut.clone());
+            context.lut_recency.touch(&key);
+            context.lut_counter.record(true);
         } else {
             keys_to_fetch.push(key);
+            context.lut_counter.record(false);
         }
     }
 
     if !keys_to_fetch.is_empty() {
-        let fetched_luts = load_lookup_tables::<T>(client, &keys_to_fetch).await?;
+        let permit = context.fetch_permit().await;
+        let fetched_luts =
+            load_lookup_tables_with_program_id::<T>(client, &keys_to_fetch, &context.program_id)
+                .await?;
+        drop(permit);
         for (key, lut) in keys_to_fetch.into_iter().zip(fetched_luts.into_iter()) {
+            if let Some(max_entries) = context.max_entries {
+                context.lut_recency.make_room(&context.lut_cache, max_entries);
+            }
             context.lut_cache.insert(key, lut.clone());
+            context.lut_recency.touch(&key);
             luts.push(lut);
         }
     }
@@ -77,13 +400,315 @@ async fn fetch_and_cache_luts<T: bytemuck::Pod + lut_owner::LutOwner>(
     Ok(luts)
 }
 
-#[derive(Clone, Debug)]
+/// The number of signatures to request for `feed`: `requested`, or by
+/// default its `min_sample_size` plus a third. The gateway can't collect more
+/// signatures than the queue has oracles, so the count is capped to
+/// `num_oracles` with a warning. Fails if the queue has no oracles or too few
+/// to meet the feed's `min_sample_size` and `min_responses`.
+fn capped_num_signatures(
+    feed: &Pubkey,
+    requested: Option<u32>,
+    feed_data: &PullFeedAccountData,
+    num_oracles: u32,
+) -> Result<u32, AnyhowError> {
+    if num_oracles == 0 {
+        return Err(anyhow!(
+            "PullFeed.fetchUpdateIx: Queue {} of feed {} has no oracles",
+            feed_data.queue,
+            feed
+        ));
+    }
+    let min_sample_size = feed_data.min_sample_size as u32;
+    let num_signatures = match requested {
+        Some(num_signatures) => num_signatures,
+        None => min_sample_size + ((min_sample_size as f64) / 3.0).ceil() as u32,
+    };
+    if num_signatures <= num_oracles {
+        return Ok(num_signatures);
+    }
+    let required = min_sample_size.max(feed_data.min_responses);
+    if num_oracles < required {
+        return Err(anyhow!(
+            "PullFeed.fetchUpdateIx: Feed {} needs {} signatures, but queue {} only has {} oracles",
+            feed,
+            required,
+            feed_data.queue,
+            num_oracles
+        ));
+    }
+    tracing::warn!(
+        feed = %feed,
+        requested = num_signatures,
+        num_oracles,
+        "PullFeed.fetchUpdateIx: capping num_signatures to the queue's oracle count"
+    );
+    Ok(num_oracles)
+}
+
+/// The length of the compact-u16 prefix encoding `len` in a transaction
+fn short_vec_len(len: usize) -> usize {
+    match len {
+        0..=0x7f => 1,
+        0x80..=0x3fff => 2,
+        _ => 3,
+    }
+}
+
+/// Drops repeated lookup tables, keeping the first of each key, so a table
+/// shared by the feed, queue or oracles is only referenced once.
+fn dedup_luts(luts: Vec<AddressLookupTableAccount>) -> Vec<AddressLookupTableAccount> {
+    let mut seen = std::collections::HashSet::new();
+    luts.into_iter().filter(|lut| seen.insert(lut.key)).collect()
+}
+
+/// Resolves the jobs for `feed_hash` through the context's job cache.
+/// Concurrent cold-cache callers for the same hash share a single in-flight
+/// crossbar fetch.
+async fn fetch_jobs(
+    context: Arc<SbContext>,
+    crossbar: &CrossbarClient,
+    feed_hash: [u8; 32],
+) -> Result<Vec<OracleJob>, AnyhowError> {
+    load_jobs(&context, feed_hash, move || async move {
+        let jobs_data = crossbar
+            .fetch(&hex::encode(feed_hash))
+            .await
+            .context("PullFeed.fetchUpdateIx: Failed to fetch jobs")?;
+        let jobs = jobs_data
+            .get("jobs")
+            .ok_or_else(|| anyhow!("PullFeed.fetchUpdateIx: Missing jobs in response"))?;
+        let jobs: Vec<OracleJob> = serde_json::from_value(jobs.clone())
+            .context("PullFeed.fetchUpdateIx: Failed to deserialize jobs")?;
+        Ok::<Vec<OracleJob>, AnyhowError>(jobs)
+    })
+    .await
+}
+
+/// Resolves the jobs for `feed_hash` through the context's job cache, calling
+/// `fetch` only if they are not cached yet. Of concurrent cold-cache callers,
+/// only the first runs its `fetch`; the others wait for its result.
+async fn load_jobs<F, Fut>(
+    context: &SbContext,
+    feed_hash: [u8; 32],
+    fetch: F,
+) -> Result<Vec<OracleJob>, AnyhowError>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<Vec<OracleJob>, AnyhowError>>,
+{
+    let cell = job_cell(context, feed_hash);
+    context.job_counter.record(cell.initialized());
+    let jobs = cell.get_or_try_init(fetch).await?;
+    Ok(jobs.clone())
+}
+
+/// Seeds the context's job cache for `feed_hash` with jobs the caller
+/// already has. An existing cache entry is left untouched.
+fn cache_jobs(context: &SbContext, feed_hash: [u8; 32], jobs: Vec<OracleJob>) {
+    let _ = job_cell(context, feed_hash).set(jobs);
+}
+
+/// The job cache cell for `feed_hash`, created if missing. The cell is cloned
+/// out of the map so the shard lock is released before awaiting it.
+fn job_cell(context: &SbContext, feed_hash: [u8; 32]) -> Arc<OnceCell<Vec<OracleJob>>> {
+    if !context.job_cache.contains_key(&feed_hash) {
+        if let Some(max_entries) = context.max_entries {
+            context.job_recency.make_room(&context.job_cache, max_entries);
+        }
+    }
+    context.job_recency.touch(&feed_hash);
+    context
+        .job_cache
+        .entry(feed_hash)
+        .or_insert_with(|| Arc::new(OnceCell::new()))
+        .value()
+        .clone()
+}
+
+/// Loads a feed through the context's `pull_feed_cache`, re-fetching it once
+/// it is older than the context's `pull_feed_ttl`
+async fn fetch_feed(
+    context: &SbContext,
+    client: &impl AccountFetcher,
+    feed: &Pubkey,
+) -> Result<PullFeedAccountData, AnyhowError> {
+    expire_stale_feed(context, feed);
+    let cell = pull_feed_cell(context, feed);
+    context.pull_feed_counter.record(cell.initialized());
+    let data = *cell
+        .get_or_try_init(|| async {
+            let _permit = context.fetch_permit().await;
+            let data = PullFeed::load_data(client, feed).await?;
+            context.pull_feed_loaded_at.insert(*feed, Instant::now());
+            Ok::<PullFeedAccountData, AnyhowError>(data)
+        })
+        .await?;
+    Ok(data)
+}
+
+/// Evicts `feed` from the context once it is older than `pull_feed_ttl`
+fn expire_stale_feed(context: &SbContext, feed: &Pubkey) {
+    if let Some(ttl) = context.pull_feed_ttl {
+        let expired = context
+            .pull_feed_loaded_at
+            .get(feed)
+            .map(|loaded_at| loaded_at.elapsed() >= ttl)
+            .unwrap_or(false);
+        if expired {
+            context.invalidate_feed(feed);
+        }
+    }
+}
+
+/// The pull feed cache cell for `feed`, created if missing. The cell is cloned
+/// out of the map so the shard lock is released before awaiting it.
+fn pull_feed_cell(context: &SbContext, feed: &Pubkey) -> Arc<OnceCell<PullFeedAccountData>> {
+    if !context.pull_feed_cache.contains_key(feed) {
+        if let Some(max_entries) = context.max_entries {
+            let evicted = context
+                .pull_feed_recency
+                .make_room(&context.pull_feed_cache, max_entries);
+            for key in evicted {
+                context.pull_feed_loaded_at.remove(&key);
+            }
+        }
+    }
+    context.pull_feed_recency.touch(feed);
+    context
+        .pull_feed_cache
+        .entry(*feed)
+        .or_insert_with(|| Arc::new(OnceCell::new()))
+        .value()
+        .clone()
+}
+
+/// Converts a value to the program's 18 decimal fixed point representation.
+/// Values too large to carry 18 decimals are only partially rescaled by
+/// `rust_decimal`, so those map to the `i128::MAX` missing-data sentinel
+/// rather than submitting a misscaled mantissa.
+fn to_submission_value(mut val: Decimal) -> i128 {
+    val.rescale(PRECISION);
+    if val.scale() != PRECISION {
+        return i128::MAX;
+    }
+    val.mantissa()
+}
+
+/// Drops repeated responses from the same oracle, which the program rejects
+/// as duplicate accounts. The first response from each oracle is kept,
+/// unless it failed and a later one succeeded.
+fn dedup_oracle_responses(responses: Vec<OracleResponse>) -> Vec<OracleResponse> {
+    let mut deduped: Vec<OracleResponse> = Vec::with_capacity(responses.len());
+    for resp in responses {
+        match deduped.iter_mut().find(|x| x.oracle == resp.oracle) {
+            Some(existing) => {
+                tracing::debug!(
+                    oracle = %resp.oracle,
+                    "PullFeed.dedup_oracle_responses: dropping duplicate response"
+                );
+                if existing.value.is_none() && resp.value.is_some() {
+                    *existing = resp;
+                }
+            }
+            None => deduped.push(resp),
+        }
+    }
+    deduped
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct OracleResponse {
     pub value: Option<Decimal>,
     pub error: String,
+    #[serde(with = "crate::serde_helpers::pubkey_string")]
     pub oracle: Pubkey,
+    #[serde(with = "crate::serde_helpers::base64_bytes")]
     pub signature: [u8; 64],
     pub recovery_id: u8,
+    /// The oracle's unix timestamp for the value, when the gateway reports one
+    #[serde(default)]
+    pub timestamp: Option<i64>,
+}
+
+impl std::fmt::Display for OracleResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "oracle={}", self.oracle)?;
+        match self.value {
+            Some(value) => write!(f, " value={}", value)?,
+            None => write!(f, " value=none")?,
+        }
+        write!(
+            f,
+            " signature={} recovery_id={}",
+            base64.encode(self.signature),
+            self.recovery_id
+        )?;
+        if let Some(timestamp) = self.timestamp {
+            write!(f, " timestamp={}", timestamp)?;
+        }
+        if !self.error.is_empty() {
+            write!(f, " error={}", self.error)?;
+        }
+        Ok(())
+    }
+}
+
+/// A failed response, or a value too large to carry 18 decimals, becomes the
+/// `i128::MAX` missing-data sentinel.
+impl From<&OracleResponse> for Submission {
+    fn from(resp: &OracleResponse) -> Self {
+        Submission {
+            value: resp.value.map_or(i128::MAX, to_submission_value),
+            signature: resp.signature,
+            recovery_id: resp.recovery_id,
+            offset: 0,
+        }
+    }
+}
+
+/// How successful oracle responses are combined into a single value when
+/// reading off-chain. The on-chain program always uses the median.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Aggregation {
+    #[default]
+    Median,
+    Mean,
+    Min,
+    Max,
+}
+
+impl Aggregation {
+    /// Combine the given values, returning `None` if there are none
+    /// (or if the computation overflows).
+    pub fn apply(&self, values: &[Decimal]) -> Option<Decimal> {
+        if values.is_empty() {
+            return None;
+        }
+        match self {
+            Aggregation::Median => {
+                let mut sorted = values.to_vec();
+                sorted.sort();
+                let mid = sorted.len() / 2;
+                if sorted.len() % 2 == 0 {
+                    (sorted[mid - 1] / Decimal::TWO).checked_add(sorted[mid] / Decimal::TWO)
+                } else {
+                    Some(sorted[mid])
+                }
+            }
+            Aggregation::Mean => values
+                .iter()
+                .try_fold(Decimal::ZERO, |acc, x| acc.checked_add(*x))?
+                .checked_div(Decimal::from(values.len())),
+            Aggregation::Min => values.iter().min().copied(),
+            Aggregation::Max => values.iter().max().copied(),
+        }
+    }
+
+    /// Combine the successful values of the given oracle responses
+    pub fn aggregate(&self, responses: &[OracleResponse]) -> Option<Decimal> {
+        let values: Vec<Decimal> = responses.iter().filter_map(|x| x.value).collect();
+        self.apply(&values)
+    }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -93,41 +718,951 @@ pub struct FetchUpdateParams {
     pub gateway: Gateway,
     pub crossbar: Option<CrossbarClient>,
     pub num_signatures: Option<u32>,
-    pub debug: Option<bool>,
+    /// Fail if fewer than this many oracles respond successfully
+    pub min_successes: Option<usize>,
+    /// The feed's jobs, if already known. When set, crossbar is not queried
+    /// and the jobs are added to the context's job cache.
+    pub jobs: Option<Vec<OracleJob>>,
+    /// Ask oracles to anchor their values to their clock. Each response then
+    /// carries the oracle's `timestamp`.
+    pub use_timestamp: bool,
+    /// Fail if the whole fetch, including every RPC and gateway request,
+    /// takes longer than this
+    pub deadline: Option<Duration>,
+    /// The mint oracle rewards are paid in. Defaults to the queue's mint.
+    pub reward_mint: Option<Pubkey>,
+    /// The token program owning the reward mint. Resolved from the mint
+    /// account if `None`.
+    pub token_program: Option<Pubkey>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FetchUpdateManyParams {
+    pub feeds: Vec<Pubkey>,
+    pub payer: Pubkey,
+    pub gateway: Gateway,
+    pub crossbar: Option<CrossbarClient>,
+    pub num_signatures: Option<u32>,
+    /// Per-feed signature counts, replacing `num_signatures` and the
+    /// `min_sample_size` based default for the listed feeds. Never below the
+    /// feed's `min_sample_size`. The gateway signs every feed in a request
+    /// with the same number of oracles, so `fetch_update_many_ixs` groups
+    /// feeds by signature count. `fetch_update_many_ix` instead requests the
+    /// largest count of all its feeds, raising lower overrides with a warning.
+    pub num_signatures_overrides: Vec<(Pubkey, u32)>,
+    /// Ask oracles to anchor their values to their clock, see
+    /// `FetchUpdateParams::use_timestamp`
+    pub use_timestamp: bool,
+    /// The mint oracle rewards are paid in. Defaults to the queue's mint.
+    pub reward_mint: Option<Pubkey>,
+    /// The token program owning the reward mint. Resolved from the mint
+    /// account if `None`.
+    pub token_program: Option<Pubkey>,
+}
+
+impl FetchUpdateManyParams {
+    /// The number of signatures to request for `feed`
+    fn num_signatures_for(&self, feed: &Pubkey, data: &PullFeedAccountData) -> u32 {
+        let min_sample_size = data.min_sample_size as u32;
+        if let Some((_, n)) = self.num_signatures_overrides.iter().find(|(k, _)| k == feed) {
+            return (*n).max(min_sample_size);
+        }
+        let lower_bound = min_sample_size + ((data.min_sample_size as f64) / 3.0).ceil() as u32;
+        self.num_signatures.unwrap_or(1).max(lower_bound)
+    }
+}
+
+/// The outcome of `PullFeed::fetch_update_report`: the update instruction
+/// along with which oracles succeeded and why the others failed.
+#[derive(Clone, Debug)]
+pub struct FetchUpdateReport {
+    /// The submit instruction for every oracle response
+    pub ix: Instruction,
+    /// Every oracle response, in the order they appear in `ix`
+    pub responses: Vec<OracleResponse>,
+    /// The last successful evaluations reported by oracles that failed this
+    /// time. Each keeps the `oracle_pubkey` of the oracle that produced it.
+    pub recent_successes: Vec<FeedEvalResponse>,
+    pub luts: Vec<AddressLookupTableAccount>,
+    /// The number of signatures requested, after capping to the queue's oracles
+    pub num_signatures: u32,
+}
+
+/// The outcome of `PullFeed::fetch_update_many_report`: the update instruction
+/// along with which feeds received enough valid oracle values.
+#[derive(Clone, Debug)]
+pub struct FetchUpdateManyReport {
+    pub ix: Instruction,
+    pub luts: Vec<AddressLookupTableAccount>,
+    /// Each feed, in instruction order, and whether at least its
+    /// `min_responses` oracles returned a value for it
+    pub feed_statuses: Vec<(Pubkey, bool)>,
+    /// Each oracle, in instruction order, and the timestamp it reported
+    pub oracle_timestamps: Vec<(Pubkey, Option<i64>)>,
+}
+
+impl FetchUpdateManyReport {
+    /// The feeds that did not receive enough valid values
+    pub fn failed_feeds(&self) -> Vec<Pubkey> {
+        self.feed_statuses
+            .iter()
+            .filter(|(_, ok)| !ok)
+            .map(|(feed, _)| *feed)
+            .collect()
+    }
+}
+
+impl FetchUpdateReport {
+    /// The responses that produced a value
+    pub fn successes(&self) -> Vec<OracleResponse> {
+        self.responses
+            .iter()
+            .filter(|x| x.value.is_some())
+            .cloned()
+            .collect()
+    }
+
+    /// The oracles that failed to produce a value and their errors
+    pub fn failures(&self) -> Vec<(Pubkey, String)> {
+        self.responses
+            .iter()
+            .filter(|x| x.value.is_none())
+            .map(|x| (x.oracle, x.error.clone()))
+            .collect()
+    }
+
+    pub fn num_successes(&self) -> usize {
+        self.responses.iter().filter(|x| x.value.is_some()).count()
+    }
+
+    /// The values of `recent_successes` with the oracle that produced each.
+    /// These are stale, but may serve as a best effort fallback when too few
+    /// oracles respond.
+    pub fn recent_success_values(&self) -> Vec<(Pubkey, Decimal)> {
+        self.recent_successes
+            .iter()
+            .filter_map(|x| {
+                let oracle: [u8; 32] = hex::decode(&x.oracle_pubkey).ok()?.try_into().ok()?;
+                let value = x.success_value.parse::<i128>().ok()?;
+                let value = Decimal::try_from_i128_with_scale(value, PRECISION).ok()?;
+                Some((Pubkey::new_from_array(oracle), value))
+            })
+            .collect()
+    }
+
+    /// Combines the live successes, falling back to `recent_success_values`
+    /// when there are none
+    pub fn best_effort_value(&self, aggregation: Aggregation) -> Option<Decimal> {
+        aggregation.aggregate(&self.responses).or_else(|| {
+            let values: Vec<Decimal> = self
+                .recent_success_values()
+                .into_iter()
+                .map(|(_, value)| value)
+                .collect();
+            aggregation.apply(&values)
+        })
+    }
+
+    fn failures_string(&self) -> String {
+        self.failures()
+            .iter()
+            .map(|(oracle, error)| format!("{}: {}", oracle, error))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SolanaSubmitSignaturesParams {
+    pub queue: Pubkey,
+    pub feed: Pubkey,
+    pub payer: Pubkey,
+    /// The mint oracle rewards are paid in. Defaults to `NATIVE_MINT`.
+    #[serde(default)]
+    pub reward_mint: Option<Pubkey>,
+    /// The token program owning `reward_mint`. Defaults to `SPL_TOKEN_PROGRAM_ID`.
+    #[serde(default)]
+    pub token_program: Option<Pubkey>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct InitFeedParams {
+    /// The new feed account. Pull feeds are plain keypair accounts, so this
+    /// key must also sign the init transaction.
+    pub feed: Pubkey,
+    pub queue: Pubkey,
+    pub authority: Pubkey,
+    pub payer: Pubkey,
+    pub feed_hash: [u8; 32],
+    pub name: [u8; 32],
+    pub min_sample_size: u8,
+    /// The maximum variance between oracle responses, scaled by 1e9
+    pub max_variance: u64,
+    pub min_responses: u32,
+    pub max_staleness: u32,
+    /// A recent slot, used to derive the feed's address lookup table
+    pub recent_slot: u64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SetFeedConfigsParams {
+    pub feed: Pubkey,
+    /// The feed's current authority, which must sign
+    pub authority: Pubkey,
+    pub feed_hash: Option<[u8; 32]>,
+    /// Transfers the feed to a new authority
+    pub new_authority: Option<Pubkey>,
+    /// The maximum variance between oracle responses, scaled by 1e9
+    pub max_variance: Option<u64>,
+    pub min_responses: Option<u32>,
+    pub name: Option<[u8; 32]>,
+    pub min_sample_size: Option<u8>,
+    pub max_staleness: Option<u32>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CloseFeedParams {
+    pub feed: Pubkey,
+    /// The feed's authority, which must sign
+    pub authority: Pubkey,
+    /// Signs and receives the rent of the closed feed
+    pub payer: Pubkey,
+    /// The feed's `lut_slot`, used to derive the lookup table closed with it
+    pub lut_slot: u64,
 }
 
-#[derive(Clone, Debug, Default)]
-pub struct FetchUpdateManyParams {
-    pub feeds: Vec<Pubkey>,
-    pub payer: Pubkey,
-    pub gateway: Gateway,
-    pub crossbar: Option<CrossbarClient>,
-    pub num_signatures: Option<u32>,
-    pub debug: Option<bool>,
-}
+impl CloseFeedParams {
+    /// The params closing `feed`, whose account data is `data`
+    pub fn from_feed(feed: Pubkey, data: &PullFeedAccountData, payer: Pubkey) -> Self {
+        Self {
+            feed,
+            authority: data.authority,
+            payer,
+            lut_slot: data.lut_slot,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ScanFeedsParams {
+    /// Only return feeds owned by this authority
+    pub authority: Option<Pubkey>,
+    /// Only return feeds bound to this queue
+    pub queue: Option<Pubkey>,
+}
+
+/// The subset of a pull feed account fetched by `PullFeed::scan`.
+#[derive(Clone, Debug)]
+pub struct PullFeedSummary {
+    pub pubkey: Pubkey,
+    pub feed_hash: [u8; 32],
+    pub name: [u8; 32],
+    pub result: CurrentResult,
+}
+
+/// A feed misconfiguration detected before any gateway is called. Returned
+/// inside the `AnyhowError` of the fetch functions, so callers can match it
+/// with `err.downcast_ref::<FeedError>()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FeedError {
+    /// The feed's jobs resolved to an empty list
+    NoJobs { feed_hash: String },
+}
+
+impl std::fmt::Display for FeedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FeedError::NoJobs { feed_hash } => write!(f, "feed {} has no jobs", feed_hash),
+        }
+    }
+}
+
+impl std::error::Error for FeedError {}
+
+/// The accounts an update reads, loaded together by `PullFeed::load_update_accounts`.
+#[derive(Clone, Debug)]
+pub struct UpdateAccounts {
+    pub feed: PullFeedAccountData,
+    pub queue: QueueAccountData,
+    pub slothash: SlotHash,
+}
+
+/// Cancels a websocket subscription once the stream holding it is dropped
+struct Unsubscribe(Option<UnsubscribeFn>);
+
+impl Drop for Unsubscribe {
+    fn drop(&mut self) {
+        if let (Some(unsubscribe), Ok(handle)) =
+            (self.0.take(), tokio::runtime::Handle::try_current())
+        {
+            handle.spawn(unsubscribe());
+        }
+    }
+}
+
+pub struct PullFeed;
+
+impl PullFeed {
+    /// Loads the feed at the client's configured commitment
+    pub async fn load_data(
+        client: &impl AccountFetcher,
+        key: &Pubkey,
+    ) -> Result<PullFeedAccountData, AnyhowError> {
+        Self::load_data_with_commitment(client, key, client.commitment()).await
+    }
+
+    /// Loads the feed at the given commitment level
+    pub async fn load_data_with_commitment(
+        client: &impl AccountFetcher,
+        key: &Pubkey,
+        commitment: CommitmentConfig,
+    ) -> Result<PullFeedAccountData, AnyhowError> {
+        let account = client
+            .get_account_with_commitment(key, commitment)
+            .await
+            .map_err(|_| anyhow!("PullFeed.load_data: Account not found"))?
+            .ok_or_else(|| anyhow!("PullFeed.load_data: Account not found"))?;
+        Self::parse_data(&account.data)
+    }
+
+    /// Parses raw pull feed account data, including the 8 byte discriminator,
+    /// failing if the data is not a pull feed account, e.g. a queue passed by mistake
+    pub fn parse_data(data: &[u8]) -> Result<PullFeedAccountData, AnyhowError> {
+        PullFeedAccountData::try_from(data)
+    }
+
+    /// Subscribes to changes of `feed` over websocket, yielding the decoded
+    /// account on every update. Notifications that fail to decode are logged
+    /// and skipped. The stream ends when the websocket disconnects, see
+    /// `subscribe_with_reconnect` to resubscribe automatically.
+    pub async fn subscribe<'a>(
+        pubsub_client: &'a PubsubClient,
+        feed: &Pubkey,
+    ) -> Result<impl Stream<Item = PullFeedAccountData> + 'a, AnyhowError> {
+        let feed = *feed;
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..Default::default()
+        };
+        let (notifications, unsubscribe) = pubsub_client
+            .account_subscribe(&feed, Some(config))
+            .await
+            .map_err(|e| anyhow!("PullFeed.subscribe: Failed to subscribe to {}: {}", feed, e))?;
+        let unsubscribe = Unsubscribe(Some(unsubscribe));
+        Ok(notifications.filter_map(move |notification| {
+            let _unsubscribe = &unsubscribe;
+            future::ready(Self::decode_notification(&feed, &notification.value))
+        }))
+    }
+
+    /// Subscribes to changes of every feed in `feeds` over one websocket,
+    /// yielding each decoded update tagged with its feed. Duplicate feeds are
+    /// subscribed once, and a notification that fails to decode is skipped
+    /// without ending the stream.
+    pub async fn subscribe_many<'a>(
+        pubsub_client: &'a PubsubClient,
+        feeds: &[Pubkey],
+    ) -> Result<impl Stream<Item = (Pubkey, PullFeedAccountData)> + 'a, AnyhowError> {
+        let mut seen = std::collections::HashSet::new();
+        let feeds: Vec<Pubkey> = feeds.iter().copied().filter(|x| seen.insert(*x)).collect();
+        let subscriptions = try_join_all(feeds.into_iter().map(|feed| async move {
+            let updates = Self::subscribe(pubsub_client, &feed).await?;
+            Ok::<_, AnyhowError>(Box::pin(updates.map(move |data| (feed, data))))
+        }))
+        .await?;
+        Ok(stream::select_all(subscriptions))
+    }
+
+    /// Like `subscribe`, but owns its websocket connection to `ws_url` and
+    /// reconnects after `reconnect_delay` whenever it drops. Updates sent
+    /// while disconnected are missed. Must be called within a Tokio runtime;
+    /// the background task stops once the stream is dropped.
+    pub fn subscribe_with_reconnect(
+        ws_url: &str,
+        feed: &Pubkey,
+        reconnect_delay: Duration,
+    ) -> impl Stream<Item = PullFeedAccountData> + Send + 'static {
+        let ws_url = ws_url.to_string();
+        let feed = *feed;
+        let (tx, rx) = tokio::sync::mpsc::channel(64);
+        tokio::spawn(async move {
+            loop {
+                match PubsubClient::new(&ws_url).await {
+                    Ok(client) => match Self::subscribe(&client, &feed).await {
+                        Ok(updates) => {
+                            let mut updates = Box::pin(updates);
+                            loop {
+                                tokio::select! {
+                                    update = updates.next() => match update {
+                                        Some(update) => {
+                                            if tx.send(update).await.is_err() {
+                                                return;
+                                            }
+                                        }
+                                        None => break,
+                                    },
+                                    _ = tx.closed() => return,
+                                }
+                            }
+                            tracing::warn!(feed = %feed, "PullFeed.subscribe_with_reconnect: Websocket disconnected");
+                        }
+                        Err(e) => tracing::warn!(feed = %feed, "{}", e),
+                    },
+                    Err(e) => {
+                        tracing::warn!(feed = %feed, "PullFeed.subscribe_with_reconnect: Failed to connect: {}", e)
+                    }
+                }
+                tokio::select! {
+                    _ = tokio::time::sleep(reconnect_delay) => {}
+                    _ = tx.closed() => return,
+                }
+            }
+        });
+        stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|x| (x, rx)) })
+    }
+
+    fn decode_notification(feed: &Pubkey, account: &UiAccount) -> Option<PullFeedAccountData> {
+        let data = match account.data.decode() {
+            Some(data) => data,
+            None => {
+                tracing::warn!(feed = %feed, "PullFeed.subscribe: Failed to decode account data");
+                return None;
+            }
+        };
+        match Self::parse_data(&data) {
+            Ok(data) => Some(data),
+            Err(e) => {
+                tracing::warn!(feed = %feed, "PullFeed.subscribe: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Loads a feed, its queue and the latest slothash with as few RPCs as
+    /// possible: one `getMultipleAccounts` when the feed's `queue` is known,
+    /// otherwise a second request for the queue once the feed is parsed.
+    /// # Arguments
+    /// * `client` - The RPC client
+    /// * `feed` - The pull feed key
+    /// * `queue` - The feed's queue, if already known. Fails if the feed
+    ///   belongs to another queue.
+    pub async fn load_update_accounts(
+        client: &impl AccountFetcher,
+        feed: &Pubkey,
+        queue: Option<Pubkey>,
+    ) -> Result<UpdateAccounts, AnyhowError> {
+        let mut keys = vec![*feed, solana_sdk::sysvar::slot_hashes::ID];
+        keys.extend(queue);
+        let mut accounts = client
+            .get_multiple_accounts_with_commitment(&keys, CommitmentConfig::confirmed())
+            .await
+            .context("PullFeed.load_update_accounts: Failed to fetch accounts")?
+            .into_iter();
+        let feed_account = accounts
+            .next()
+            .flatten()
+            .ok_or_else(|| anyhow!("PullFeed.load_update_accounts: Feed account not found"))?;
+        let slothash_account = accounts
+            .next()
+            .flatten()
+            .ok_or_else(|| anyhow!("PullFeed.load_update_accounts: Slothash sysvar not found"))?;
+        let feed_data = Self::parse_data(&feed_account.data)?;
+        if let Some(queue) = queue {
+            if queue != feed_data.queue {
+                return Err(anyhow!(
+                    "PullFeed.load_update_accounts: Feed {} belongs to queue {}, not {}",
+                    feed,
+                    feed_data.queue,
+                    queue
+                ));
+            }
+        }
+        let slothash = SlotHashSysvar::parse_latest(&slothash_account.data)?;
+
+        let queue_data = match accounts.next().flatten() {
+            Some(account) => account.data,
+            None => client
+                .get_account_data(&feed_data.queue)
+                .await
+                .context("PullFeed.load_update_accounts: Failed to fetch queue")?,
+        };
+        let queue_data = QueueAccountData::try_from(queue_data.as_slice())?;
+
+        Ok(UpdateAccounts {
+            feed: feed_data,
+            queue: queue_data,
+            slothash,
+        })
+    }
+
+    /// Like `load_update_accounts`, going through `context`'s caches. Fresh
+    /// cached entries are reused and everything missing is fetched together
+    /// in one `getMultipleAccounts`, then cached. Only a queue unknown until
+    /// the feed is parsed costs a second request.
+    /// # Arguments
+    /// * `context` - The context whose caches are read and filled
+    /// * `client` - The RPC client
+    /// * `feed` - The pull feed key
+    pub async fn load_update_accounts_cached(
+        context: &SbContext,
+        client: &impl AccountFetcher,
+        feed: &Pubkey,
+    ) -> Result<UpdateAccounts, AnyhowError> {
+        let slothash = match *context.slothash_cache.lock().unwrap() {
+            Some((fetched_at, slothash)) if fetched_at.elapsed() < SLOTHASH_CACHE_TTL => {
+                Some(slothash)
+            }
+            _ => None,
+        };
+        expire_stale_feed(context, feed);
+        let feed_cell = pull_feed_cell(context, feed);
+        let feed_data = feed_cell.get().copied();
+        context.pull_feed_counter.record(feed_data.is_some());
+        let queue_cell = feed_data.map(|data| context.queue_cell(&data.queue));
+        let queue_data = queue_cell.as_ref().and_then(|cell| cell.get().copied());
+        if let Some(queue_cell) = &queue_cell {
+            context.queue_counter.record(queue_cell.initialized());
+        }
+
+        if let (Some(feed_data), Some(queue_data), Some(slothash)) =
+            (feed_data, queue_data, slothash)
+        {
+            return Ok(UpdateAccounts {
+                feed: feed_data,
+                queue: queue_data,
+                slothash,
+            });
+        }
+
+        // Only what is missing is fetched, in this order
+        let mut keys = Vec::with_capacity(3);
+        if feed_data.is_none() {
+            keys.push(*feed);
+        }
+        if slothash.is_none() {
+            keys.push(solana_sdk::sysvar::slot_hashes::ID);
+        }
+        if let (Some(feed_data), None) = (feed_data, queue_data) {
+            keys.push(feed_data.queue);
+        }
+        let permit = context.fetch_permit().await;
+        let mut accounts = client
+            .get_multiple_accounts_with_commitment(&keys, CommitmentConfig::confirmed())
+            .await
+            .context("PullFeed.load_update_accounts_cached: Failed to fetch accounts")?
+            .into_iter();
+        drop(permit);
+
+        let feed_data = match feed_data {
+            Some(feed_data) => feed_data,
+            None => {
+                let feed_account = accounts.next().flatten().ok_or_else(|| {
+                    anyhow!("PullFeed.load_update_accounts_cached: Feed account not found")
+                })?;
+                let feed_data = Self::parse_data(&feed_account.data)?;
+                if feed_cell.set(feed_data).is_ok() {
+                    context.pull_feed_loaded_at.insert(*feed, Instant::now());
+                }
+                feed_data
+            }
+        };
+
+        let slothash = match slothash {
+            Some(slothash) => slothash,
+            None => {
+                let slothash_account = accounts.next().flatten().ok_or_else(|| {
+                    anyhow!("PullFeed.load_update_accounts_cached: Slothash sysvar not found")
+                })?;
+                let slothash = SlotHashSysvar::parse_latest(&slothash_account.data)?;
+                *context.slothash_cache.lock().unwrap() = Some((Instant::now(), slothash));
+                slothash
+            }
+        };
+
+        let queue_data = match (queue_data, accounts.next().flatten()) {
+            (Some(queue_data), _) => queue_data,
+            (None, Some(account)) => {
+                let queue_data = QueueAccountData::try_from(account.data.as_slice())?;
+                let _ = context.queue_cell(&feed_data.queue).set(queue_data);
+                queue_data
+            }
+            (None, None) => context
+                .load_queue(client, &feed_data.queue)
+                .await
+                .context("PullFeed.load_update_accounts_cached: Failed to load queue")?,
+        };
+
+        Ok(UpdateAccounts {
+            feed: feed_data,
+            queue: queue_data,
+            slothash,
+        })
+    }
+
+    /// The compute unit limit for an update carrying `num_submissions` oracle signatures
+    pub fn compute_unit_limit(num_submissions: usize) -> u32 {
+        let per_submission =
+            SUBMIT_COMPUTE_UNITS_PER_SUBMISSION.saturating_mul(num_submissions as u32);
+        SUBMIT_BASE_COMPUTE_UNITS
+            .saturating_add(per_submission)
+            .min(MAX_COMPUTE_UNITS)
+    }
+
+    /// Prepend compute budget instructions sized to the number of submissions
+    /// to an update instruction.
+    /// # Arguments
+    /// * `update_ix` - The update instruction, e.g. from `fetch_update_ix`
+    /// * `num_submissions` - The number of oracle submissions in the update
+    /// * `compute_unit_price` - An optional priority fee in micro-lamports per compute unit
+    /// # Returns
+    /// * `(Vec<Instruction>, u32)` - The instructions and the chosen compute unit limit
+    pub fn build_update_ixs(
+        update_ix: Instruction,
+        num_submissions: usize,
+        compute_unit_price: Option<u64>,
+    ) -> (Vec<Instruction>, u32) {
+        let compute_unit_limit = Self::compute_unit_limit(num_submissions);
+        let mut ixs = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+            compute_unit_limit,
+        )];
+        if let Some(price) = compute_unit_price {
+            ixs.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+        }
+        ixs.push(update_ix);
+        (ixs, compute_unit_limit)
+    }
+
+    /// Estimate a competitive compute unit price (in micro-lamports) from the
+    /// recent prioritization fees paid for transactions writing to `accounts`,
+    /// using the `DEFAULT_PRIORITY_FEE_PERCENTILE`.
+    pub async fn estimate_priority_fee(
+        client: &impl AccountFetcher,
+        accounts: &[Pubkey],
+    ) -> Result<u64, AnyhowError> {
+        Self::estimate_priority_fee_with_percentile(
+            client,
+            accounts,
+            DEFAULT_PRIORITY_FEE_PERCENTILE,
+        )
+        .await
+    }
+
+    /// Estimate a compute unit price (in micro-lamports) at the given
+    /// `percentile` (0-100) of the recent prioritization fees paid for
+    /// transactions writing to `accounts`. Returns 0 if no fees were reported.
+    pub async fn estimate_priority_fee_with_percentile(
+        client: &impl AccountFetcher,
+        accounts: &[Pubkey],
+        percentile: u8,
+    ) -> Result<u64, AnyhowError> {
+        if percentile > 100 {
+            return Err(anyhow!(
+                "PullFeed.estimate_priority_fee: percentile {} out of range",
+                percentile
+            ));
+        }
+        let recent_fees = client
+            .get_recent_prioritization_fees(accounts)
+            .await
+            .context("PullFeed.estimate_priority_fee: failed to fetch prioritization fees")?;
+        let mut fees: Vec<u64> = recent_fees
+            .iter()
+            .map(|fee| fee.prioritization_fee)
+            .collect();
+        if fees.is_empty() {
+            return Ok(0);
+        }
+        fees.sort_unstable();
+        let idx = (fees.len() - 1) * percentile as usize / 100;
+        Ok(fees[idx])
+    }
+
+    /// The serialized size of a v0 transaction carrying a
+    /// `submit_response_many` update for `num_feeds` feeds signed by
+    /// `num_oracles` oracles, plus the compute unit limit and price
+    /// instructions.
+    ///
+    /// This assumes the layout `fetch_update_many_ix` produces: the payer,
+    /// the on-demand program and the compute budget program are the only
+    /// static keys, and every other account is resolved through one table per
+    /// oracle (the oracle and its stats), one per feed and one for the queue's
+    /// accounts. Oracles sign every feed in the group, so their accounts and
+    /// tables are counted once regardless of the number of feeds. A
+    /// transaction resolving more keys statically, e.g. because a table is
+    /// missing, is larger than estimated.
+    pub fn estimate_update_many_tx_size(num_feeds: usize, num_oracles: usize) -> usize {
+        // signature count + payer signature
+        let signatures = 1 + 64;
+        // version prefix + header + static keys + recent blockhash
+        let message_header = 1 + 3 + (1 + 32 * 3) + 32;
+        // instruction count + set_compute_unit_limit + set_compute_unit_price
+        let compute_budget_ixs = 1 + (3 + 5) + (3 + 9);
+        // discriminator + slot + vec length prefix + per oracle submissions
+        let data_len = 8 + 8 + 4 + num_oracles * (4 + 16 * num_feeds + 64 + 1);
+        // the 8 fixed accounts + a feed per feed + an oracle and its stats per oracle
+        let num_accounts = 8 + num_feeds + 2 * num_oracles;
+        let submit_ix =
+            1 + short_vec_len(num_accounts) + num_accounts + short_vec_len(data_len) + data_len;
+        // each table is a key and two index length prefixes, and every account
+        // but the payer is one index
+        let num_luts = num_oracles + num_feeds + 1;
+        let lookups = short_vec_len(num_luts) + num_luts * (32 + 1 + 1) + (num_accounts - 1);
+        signatures + message_header + compute_budget_ixs + submit_ix + lookups
+    }
+
+    /// The largest number of feeds that fit in a single update transaction
+    /// signed by `num_oracles` oracles. Returns 0 if not even one feed fits.
+    pub fn max_feeds_per_update(num_oracles: usize) -> usize {
+        let mut num_feeds = 0;
+        while Self::estimate_update_many_tx_size(num_feeds + 1, num_oracles) <= PACKET_DATA_SIZE {
+            num_feeds += 1;
+        }
+        num_feeds
+    }
+
+    /// Splits `(feed, num_signatures)` pairs into update groups, in order of
+    /// first appearance. Each gateway request signs all of its feeds with the
+    /// same oracles, so feeds are grouped by signature count and every group
+    /// is then chunked to fit in a transaction. Fails if a single feed with
+    /// its signatures does not fit.
+    fn group_by_num_signatures(
+        feeds: &[(Pubkey, u32)],
+    ) -> Result<Vec<(u32, Vec<Pubkey>)>, AnyhowError> {
+        let mut groups: Vec<(u32, Vec<Pubkey>)> = Vec::new();
+        for (feed, num_signatures) in feeds {
+            match groups.iter_mut().find(|(n, _)| n == num_signatures) {
+                Some((_, feeds)) => feeds.push(*feed),
+                None => groups.push((*num_signatures, vec![*feed])),
+            }
+        }
+
+        let mut chunks = Vec::new();
+        for (num_signatures, feeds) in groups {
+            let max_feeds = Self::max_feeds_per_update(num_signatures as usize);
+            if max_feeds == 0 {
+                return Err(anyhow!(
+                    "PullFeed.fetchUpdateManyIxs: a single feed with {} signatures exceeds the transaction size limit",
+                    num_signatures
+                ));
+            }
+            for chunk in feeds.chunks(max_feeds) {
+                chunks.push((num_signatures, chunk.to_vec()));
+            }
+        }
+        Ok(chunks)
+    }
+
+    /// Simulate `feed`'s jobs through crossbar without submitting anything,
+    /// as a dry run before building an update. Failed runs are `None`.
+    /// # Arguments
+    /// * `crossbar` - The crossbar client
+    /// * `network` - The cluster `feed` lives on
+    /// * `feed` - The pull feed account
+    pub async fn simulate(
+        crossbar: &CrossbarClient,
+        network: ClusterType,
+        feed: &Pubkey,
+    ) -> Result<Vec<Option<Decimal>>, AnyhowError> {
+        let responses = crossbar
+            .simulate_solana_feeds(network, &[*feed])
+            .await
+            .context("PullFeed.simulate: Failed to simulate feed")?;
+        let feed_str = feed.to_string();
+        let response = responses
+            .iter()
+            .find(|x| x.feed == feed_str)
+            .or_else(|| responses.first())
+            .ok_or_else(|| anyhow!("PullFeed.simulate: No simulation result for {}", feed))?;
+        Ok(response
+            .results
+            .iter()
+            .map(|x| x.and_then(simulated_to_decimal))
+            .collect())
+    }
+
+    /// Enumerate pull feed accounts with `getProgramAccounts`, downloading only
+    /// the `feed_hash` through `result` region of each account rather than
+    /// the full account data.
+    /// # Arguments
+    /// * `client` - The RPC client
+    /// * `params` - Optional authority / queue filters
+    pub async fn scan(
+        client: &impl AccountFetcher,
+        params: ScanFeedsParams,
+    ) -> Result<Vec<PullFeedSummary>, AnyhowError> {
+        let start = std::mem::offset_of!(PullFeedAccountData, feed_hash);
+        let end = std::mem::offset_of!(PullFeedAccountData, result)
+            + std::mem::size_of::<CurrentResult>();
+        let name_offset = std::mem::offset_of!(PullFeedAccountData, name) - start;
+        let result_offset = std::mem::offset_of!(PullFeedAccountData, result) - start;
+
+        let mut filters = vec![
+            RpcFilterType::DataSize(8 + std::mem::size_of::<PullFeedAccountData>() as u64),
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                0,
+                &get_account_discriminator("PullFeedAccountData"),
+            )),
+        ];
+        if let Some(authority) = params.authority {
+            filters.push(RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                8 + std::mem::offset_of!(PullFeedAccountData, authority),
+                authority.as_ref(),
+            )));
+        }
+        if let Some(queue) = params.queue {
+            filters.push(RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                8 + std::mem::offset_of!(PullFeedAccountData, queue),
+                queue.as_ref(),
+            )));
+        }
+        let config = RpcProgramAccountsConfig {
+            filters: Some(filters),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                data_slice: Some(UiDataSliceConfig {
+                    offset: 8 + start,
+                    length: end - start,
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let accounts = client
+            .get_program_accounts_with_config(&SWITCHBOARD_ON_DEMAND_PROGRAM_ID, config)
+            .await
+            .context("PullFeed.scan: Failed to fetch program accounts")?;
+
+        accounts
+            .into_iter()
+            .map(|(pubkey, account)| {
+                let data = &account.data;
+                if data.len() != end - start {
+                    return Err(anyhow!("PullFeed.scan: Unexpected slice length for {}", pubkey));
+                }
+                let result = bytemuck::try_pod_read_unaligned::<CurrentResult>(
+                    &data[result_offset..result_offset + std::mem::size_of::<CurrentResult>()],
+                )
+                .map_err(|_| anyhow!("PullFeed.scan: Failed to parse result for {}", pubkey))?;
+                Ok(PullFeedSummary {
+                    pubkey,
+                    feed_hash: *arrayref::array_ref![data, 0, 32],
+                    name: *arrayref::array_ref![data, name_offset, 32],
+                    result,
+                })
+            })
+            .collect()
+    }
+
+    /// Build the instruction creating a new pull feed account along with its
+    /// reward escrow and address lookup table.
+    /// # Arguments
+    /// * `params` - The parameters of the new feed. `params.feed` should be a
+    ///   freshly generated keypair's pubkey, and that keypair must sign.
+    pub fn get_init_ix(params: InitFeedParams) -> Result<Instruction, AnyhowError> {
+        Self::get_init_ix_with_program_id(params, &SWITCHBOARD_ON_DEMAND_PROGRAM_ID)
+    }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct SolanaSubmitSignaturesParams {
-    pub queue: Pubkey,
-    pub feed: Pubkey,
-    pub payer: Pubkey,
-}
+    /// Like `get_init_ix`, for a custom program ID
+    pub fn get_init_ix_with_program_id(
+        params: InitFeedParams,
+        program_id: &Pubkey,
+    ) -> Result<Instruction, AnyhowError> {
+        let lut_signer = find_lut_signer_with_program_id(&params.feed, program_id);
+        let lut = derive_lookup_table_address(&lut_signer, params.recent_slot).0;
+        Ok(Instruction {
+            program_id: *program_id,
+            data: PullFeedInitParams {
+                feed_hash: params.feed_hash,
+                max_variance: params.max_variance,
+                min_responses: params.min_responses,
+                name: params.name,
+                recent_slot: params.recent_slot,
+                ipfs_hash: [0; 32],
+                min_sample_size: params.min_sample_size,
+                max_staleness: params.max_staleness,
+            }
+            .data(),
+            accounts: PullFeedInit {
+                pull_feed: params.feed,
+                queue: params.queue,
+                authority: params.authority,
+                payer: params.payer,
+                system_program: system_program::ID,
+                program_state: State::key_with_program_id(program_id),
+                reward_escrow: get_associated_token_address(&params.feed, &NATIVE_MINT),
+                token_program: *SPL_TOKEN_PROGRAM_ID,
+                associated_token_program: *SPL_ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID,
+                wrapped_sol_mint: *NATIVE_MINT,
+                lut_signer,
+                lut,
+                address_lookup_table_program: *ADDRESS_LOOKUP_TABLE_PROGRAM_ID,
+            }
+            .to_account_metas(None),
+        })
+    }
 
-pub struct PullFeed;
+    /// Build the instruction updating a feed's configuration. Only the
+    /// settings given in `params` are changed.
+    pub fn get_set_configs_ix(params: SetFeedConfigsParams) -> Instruction {
+        Self::get_set_configs_ix_with_program_id(params, &SWITCHBOARD_ON_DEMAND_PROGRAM_ID)
+    }
 
-impl PullFeed {
-    pub async fn load_data(
-        client: &RpcClient,
-        key: &Pubkey,
-    ) -> Result<PullFeedAccountData, AnyhowError> {
-        let account = client
-            .get_account_data(key)
-            .await
-            .map_err(|_| anyhow!("PullFeed.load_data: Account not found"))?;
-        let account = account[8..].to_vec();
-        let data = bytemuck::try_from_bytes::<PullFeedAccountData>(&account)
-            .map_err(|_| anyhow!("PullFeed.load_data: Failed to parse data"))?;
-        Ok(data.clone())
+    /// Like `get_set_configs_ix`, for a custom program ID
+    pub fn get_set_configs_ix_with_program_id(
+        params: SetFeedConfigsParams,
+        program_id: &Pubkey,
+    ) -> Instruction {
+        Instruction {
+            program_id: *program_id,
+            data: PullFeedSetConfigsParams {
+                feed_hash: params.feed_hash,
+                authority: params.new_authority,
+                max_variance: params.max_variance,
+                min_responses: params.min_responses,
+                name: params.name,
+                ipfs_hash: None,
+                min_sample_size: params.min_sample_size,
+                max_staleness: params.max_staleness,
+            }
+            .data(),
+            accounts: PullFeedSetConfigs {
+                pull_feed: params.feed,
+                authority: params.authority,
+            }
+            .to_account_metas(None),
+        }
+    }
+
+    /// Build the instruction closing a feed. The program closes the feed's
+    /// reward escrow and address lookup table along with it, returning the
+    /// rent to `params.payer`. See `CloseFeedParams::from_feed` to fill the
+    /// params from loaded feed data.
+    pub fn get_close_ix(params: CloseFeedParams) -> Instruction {
+        Self::get_close_ix_with_program_id(params, &SWITCHBOARD_ON_DEMAND_PROGRAM_ID)
+    }
+
+    /// Like `get_close_ix`, for a custom program ID
+    pub fn get_close_ix_with_program_id(params: CloseFeedParams, program_id: &Pubkey) -> Instruction {
+        let lut_signer = find_lut_signer_with_program_id(&params.feed, program_id);
+        let lut = derive_lookup_table_address(&lut_signer, params.lut_slot).0;
+        Instruction {
+            program_id: *program_id,
+            data: PullFeedCloseParams {}.data(),
+            accounts: PullFeedClose {
+                pull_feed: params.feed,
+                reward_escrow: get_associated_token_address(&params.feed, &NATIVE_MINT),
+                lut,
+                lut_signer,
+                payer: params.payer,
+                state: State::key_with_program_id(program_id),
+                authority: params.authority,
+                token_program: *SPL_TOKEN_PROGRAM_ID,
+                associated_token_program: *SPL_ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID,
+                system_program: system_program::ID,
+                address_lookup_table_program: *ADDRESS_LOOKUP_TABLE_PROGRAM_ID,
+            }
+            .to_account_metas(None),
+        }
     }
 
     pub fn get_solana_submit_signatures_ix(
@@ -135,41 +1670,49 @@ impl PullFeed {
         responses: Vec<OracleResponse>,
         params: SolanaSubmitSignaturesParams,
     ) -> Result<Instruction, AnyhowError> {
-        let mut submissions = Vec::new();
-        for resp in &responses {
-            let mut value_i128 = i128::MAX;
-            if let Some(mut val) = resp.value {
-                val.rescale(18);
-                value_i128 = val.mantissa();
-            }
-            submissions.push(Submission {
-                value: value_i128,
-                signature: resp.signature,
-                recovery_id: resp.recovery_id,
-                offset: 0,
-            });
-        }
+        Self::get_solana_submit_signatures_ix_with_program_id(
+            slot,
+            responses,
+            params,
+            &SWITCHBOARD_ON_DEMAND_PROGRAM_ID,
+        )
+    }
+
+    /// Like `get_solana_submit_signatures_ix`, for a custom program ID
+    pub fn get_solana_submit_signatures_ix_with_program_id(
+        slot: u64,
+        responses: Vec<OracleResponse>,
+        params: SolanaSubmitSignaturesParams,
+        program_id: &Pubkey,
+    ) -> Result<Instruction, AnyhowError> {
+        let reward_mint = params.reward_mint.unwrap_or(*NATIVE_MINT);
+        let token_program = params.token_program.unwrap_or(*SPL_TOKEN_PROGRAM_ID);
+        let submissions: Vec<Submission> = responses.iter().map(Submission::from).collect();
         let mut remaining_accounts = Vec::new();
         for resp in &responses {
             remaining_accounts.push(AccountMeta::new_readonly(resp.oracle, false));
         }
         for resp in responses {
-            let stats_key = OracleAccountData::stats_key(&resp.oracle);
+            let stats_key = OracleAccountData::stats_key_with_program_id(&resp.oracle, program_id);
             remaining_accounts.push(AccountMeta::new(stats_key, false));
         }
         let mut submit_ix = Instruction {
-            program_id: *SWITCHBOARD_ON_DEMAND_PROGRAM_ID,
+            program_id: *program_id,
             data: PullFeedSubmitResponseParams { slot, submissions }.data(),
             accounts: PullFeedSubmitResponse {
                 feed: params.feed,
                 queue: params.queue,
-                program_state: State::key(),
+                program_state: State::key_with_program_id(program_id),
                 recent_slothashes: solana_sdk::sysvar::slot_hashes::ID,
                 payer: params.payer,
                 system_program: system_program::ID,
-                reward_vault: get_associated_token_address(&params.queue, &NATIVE_MINT),
-                token_program: *SPL_TOKEN_PROGRAM_ID,
-                token_mint: *NATIVE_MINT,
+                reward_vault: get_associated_token_address_with_program_id(
+                    &params.queue,
+                    &reward_mint,
+                    &token_program,
+                ),
+                token_program,
+                token_mint: reward_mint,
             }
             .to_account_metas(None),
         };
@@ -179,7 +1722,7 @@ impl PullFeed {
 
     pub async fn fetch_update_ix(
         context: Arc<SbContext>,
-        client: &RpcClient,
+        client: &impl AccountFetcher,
         params: FetchUpdateParams,
     ) -> Result<
         (
@@ -190,63 +1733,136 @@ impl PullFeed {
         ),
         AnyhowError,
         > {
-        let latest_slot = SlotHashSysvar::get_latest_slothash(&client)
+        let min_successes = params.min_successes;
+        let report = PullFeed::fetch_update_report(context, client, params).await?;
+        let num_successes = report.num_successes();
+        if num_successes == 0 {
+            return Err(anyhow_ext::Error::msg(format!(
+                "PullFeed.fetchUpdateIx Failure: No successful responses; failures: [{}]",
+                report.failures_string()
+            )));
+        }
+        if let Some(min_successes) = min_successes {
+            if num_successes < min_successes {
+                return Err(anyhow!(
+                    "PullFeed.fetchUpdateIx Failure: {} of {} required successful responses; failures: [{}]",
+                    num_successes,
+                    min_successes,
+                    report.failures_string()
+                ));
+            }
+        }
+        Ok((report.ix, report.responses, num_successes, report.luts))
+    }
+
+    /// Fetch an update for a feed and send it: prepends compute budget
+    /// instructions sized to the responses, compiles a v0 transaction using
+    /// the update's lookup tables and sends it with `send_config`.
+    /// Use `fetch_update_ix` to build the transaction yourself.
+    /// # Arguments
+    /// * `client` - The RPC client
+    /// * `params` - The parameters for the fetch. `params.payer` should be the
+    ///   first signer, which pays for the transaction.
+    /// * `signers` - The transaction signers
+    /// * `compute_unit_price` - An optional priority fee in micro-lamports per
+    ///   compute unit, e.g. from `estimate_priority_fee`
+    /// * `send_config` - How the transaction is sent
+    pub async fn fetch_and_submit(
+        context: Arc<SbContext>,
+        client: &impl AccountFetcher,
+        params: FetchUpdateParams,
+        signers: &[&Keypair],
+        compute_unit_price: Option<u64>,
+        send_config: RpcSendTransactionConfig,
+    ) -> Result<Signature, AnyhowError> {
+        let (ix, responses, _, luts) = Self::fetch_update_ix(context, client, params).await?;
+        let (ixs, _) = Self::build_update_ixs(ix, responses.len(), compute_unit_price);
+        let blockhash = client
+            .get_latest_blockhash()
             .await
-            .context("PullFeed.fetchUpdateIx: Failed to fetch latest slot")?;
+            .context("PullFeed.fetchAndSubmit: Failed to fetch blockhash")?;
+        let tx = ix_to_versioned_tx(&ixs, signers, &luts, blockhash)?;
+        let signature = client
+            .send_transaction_with_config(&tx, send_config)
+            .await
+            .context("PullFeed.fetchAndSubmit: Failed to send transaction")?;
+        Ok(signature)
+    }
 
-        let feed_data = context
-            .pull_feed_cache
-            .entry(params.feed)
-            .or_insert_with(OnceCell::new)
-            .get_or_try_init(||{
-                PullFeed::load_data(&client, &params.feed)
-            })
-            .await?
-            .clone();
-
-        let feed_hash = feed_data.feed_hash;
-        let jobs = context
-            .job_cache
-            .entry(feed_hash)
-            .or_insert_with(OnceCell::new)
-            .get_or_try_init(|| {
-                let crossbar = params.crossbar.clone().unwrap_or_default();
-                async move {
-                    let jobs_data = crossbar
-                        .fetch(&hex::encode(feed_hash))
-                        .await
-                        .context("PullFeed.fetchUpdateIx: Failed to fetch jobs")?;
+    /// Fetch the oracle responses for a feed and build its update instruction,
+    /// reporting which oracles succeeded and why the others failed. Unlike
+    /// `fetch_update_ix` this does not fail when oracles fail.
+    /// # Arguments
+    /// * `client` - The RPC client
+    /// * `params` - The parameters for the fetch
+    pub async fn fetch_update_report(
+        context: Arc<SbContext>,
+        client: &impl AccountFetcher,
+        params: FetchUpdateParams,
+    ) -> Result<FetchUpdateReport, AnyhowError> {
+        match params.deadline {
+            // Timing out drops the fetch, cancelling its in-flight requests
+            Some(deadline) => tokio::time::timeout(
+                deadline,
+                Self::fetch_update_report_inner(context, client, params),
+            )
+            .await
+            .map_err(|_| anyhow!("PullFeed.fetchUpdateIx: Deadline of {:?} exceeded", deadline))?,
+            None => Self::fetch_update_report_inner(context, client, params).await,
+        }
+    }
 
-                    let jobs: Vec<OracleJob> = serde_json::from_value(jobs_data.get("jobs").unwrap().clone())
-                        .context("PullFeed.fetchUpdateIx: Failed to deserialize jobs")?;
+    async fn fetch_update_report_inner(
+        context: Arc<SbContext>,
+        client: &impl AccountFetcher,
+        params: FetchUpdateParams,
+    ) -> Result<FetchUpdateReport, AnyhowError> {
+        let update_accounts = Self::load_update_accounts_cached(&context, client, &params.feed)
+            .await
+            .context("PullFeed.fetchUpdateIx: Failed to load the feed, queue and slothash")?;
+        let latest_slot = update_accounts.slothash;
+        let feed_data = update_accounts.feed;
 
-                    Ok::<Vec<OracleJob>, AnyhowError>(jobs)
-                }
-            })
-        .await?.clone();
+        let jobs = match params.jobs.clone() {
+            Some(jobs) => {
+                cache_jobs(&context, feed_data.feed_hash, jobs.clone());
+                jobs
+            }
+            None => {
+                let crossbar = params.crossbar.clone().unwrap_or_default();
+                fetch_jobs(context.clone(), &crossbar, feed_data.feed_hash).await?
+            }
+        };
 
         let encoded_jobs = encode_jobs(jobs);
+        if encoded_jobs.is_empty() {
+            return Err(FeedError::NoJobs {
+                feed_hash: feed_data.feed_hash(),
+            }
+            .into());
+        }
         let gateway = params.gateway;
 
-        let num_signatures = if params.num_signatures.is_none() {
-            (feed_data.min_sample_size as f64 + ((feed_data.min_sample_size as f64) / 3.0).ceil()) as u32
-        } else {
-            params.num_signatures.unwrap()
-        };
+        let num_signatures = capped_num_signatures(
+            &params.feed,
+            params.num_signatures,
+            &feed_data,
+            update_accounts.queue.oracle_keys_len,
+        )?;
 
+        let started = Instant::now();
         let price_signatures = gateway
             .fetch_signatures_from_encoded(FetchSignaturesParams {
-                recent_hash: Some(bs58::encode(latest_slot.hash.clone()).into_string()),
+                recent_hash: Some(latest_slot.hash_base58()),
                 encoded_jobs: encoded_jobs.clone(),
                 num_signatures: num_signatures,
                 max_variance: Some((feed_data.max_variance / 1_000_000_000) as u32),
                 min_responses: Some(feed_data.min_responses),
-                use_timestamp: Some(false),
+                use_timestamp: Some(params.use_timestamp),
             })
             .await
             .context("PullFeed.fetchUpdateIx: Failed to fetch signatures")?;
 
-        let mut num_successes = 0;
         let oracle_responses: Vec<OracleResponse> = price_signatures
             .responses
             .iter()
@@ -254,7 +1870,6 @@ impl PullFeed {
                 let value = x.success_value.parse::<i128>().ok();
                 let mut formatted_value = None;
                 if let Some(val) = value {
-                    num_successes += 1;
                     formatted_value = Some(Decimal::from_i128_with_scale(val, 18));
                 }
                 OracleResponse {
@@ -272,28 +1887,46 @@ impl PullFeed {
                         .unwrap_or(Vec::new())
                         .try_into()
                         .unwrap_or([0; 64]),
+                    timestamp: x.timestamp,
                 }
             })
         .collect();
+        let mut oracle_responses = dedup_oracle_responses(oracle_responses);
+        // Sort by oracle so identical fetches build byte-identical instructions.
+        // Submissions and remaining accounts are both derived from this order.
+        oracle_responses.sort_by_key(|x| x.oracle);
 
-        if params.debug.unwrap_or(false) {
-            println!("priceSignatures: {:?}", price_signatures);
-        }
-
-        if num_successes == 0 {
-            return Err(anyhow_ext::Error::msg(format!(
-                "PullFeed.fetchUpdateIx Failure: No successful responses"
-            )));
+        tracing::debug!(
+            feed = %params.feed,
+            num_oracles = oracle_responses.len(),
+            num_successes = oracle_responses.iter().filter(|x| x.value.is_some()).count(),
+            latency_ms = started.elapsed().as_millis() as u64,
+            "PullFeed.fetchUpdateIx: fetched signatures"
+        );
+        for resp in &oracle_responses {
+            tracing::trace!(feed = %params.feed, "oracle response: {}", resp);
         }
 
-        let submit_signatures_ix = PullFeed::get_solana_submit_signatures_ix(
+        let (reward_mint, token_program) =
+            fetch_reward_mint(
+                &context,
+                client,
+                &feed_data.queue,
+                params.reward_mint,
+                params.token_program,
+            )
+            .await?;
+        let submit_signatures_ix = PullFeed::get_solana_submit_signatures_ix_with_program_id(
             latest_slot.slot,
             oracle_responses.clone(),
             SolanaSubmitSignaturesParams {
                 feed: params.feed,
                 queue: feed_data.queue,
                 payer: params.payer,
+                reward_mint: Some(reward_mint),
+                token_program: Some(token_program),
             },
+            &context.program_id,
         )
             .context("PullFeed.fetchUpdateIx: Failed to create submit signatures instruction")?;
 
@@ -313,8 +1946,28 @@ impl PullFeed {
         let mut luts = oracle_luts;
         luts.extend(pull_feed_lut);
         luts.extend(queue_lut);
+        let luts = dedup_luts(luts);
+
+        let recent_successes = price_signatures
+            .responses
+            .iter()
+            .flat_map(|x| {
+                x.recent_successes_if_failed.iter().cloned().map(|mut success| {
+                    if success.oracle_pubkey.is_empty() {
+                        success.oracle_pubkey = x.oracle_pubkey.clone();
+                    }
+                    success
+                })
+            })
+            .collect();
 
-        Ok((submit_signatures_ix, oracle_responses, num_successes, luts))
+        Ok(FetchUpdateReport {
+            ix: submit_signatures_ix,
+            responses: oracle_responses,
+            recent_successes,
+            luts,
+            num_signatures,
+        })
     }
 
     /// Fetch the oracle responses and format them into a Solana instruction.
@@ -325,48 +1978,46 @@ impl PullFeed {
     /// * `params` - The parameters for the fetch
     pub async fn fetch_update_many_ix(
         context: Arc<SbContext>,
-        client: &RpcClient,
+        client: &impl AccountFetcher,
         params: FetchUpdateManyParams,
     ) -> Result<(Instruction, Vec<AddressLookupTableAccount>), AnyhowError> {
+        let report = PullFeed::fetch_update_many_report(context, client, params).await?;
+        Ok((report.ix, report.luts))
+    }
+
+    /// Like `fetch_update_many_ix`, additionally reporting which feeds received
+    /// enough valid oracle values. Feeds without them are still part of the
+    /// instruction, with `i128::MAX` sentinels the program skips.
+    /// # Arguments
+    /// * `client` - The RPC client
+    /// * `params` - The parameters for the fetch
+    pub async fn fetch_update_many_report(
+        context: Arc<SbContext>,
+        client: &impl AccountFetcher,
+        params: FetchUpdateManyParams,
+    ) -> Result<FetchUpdateManyReport, AnyhowError> {
         let crossbar = params.crossbar.clone().unwrap_or_default();
         let gateway = params.gateway;
-        let mut num_signatures = params.num_signatures.unwrap_or(1);
+        let mut feed_num_signatures = Vec::new();
         let mut feed_configs = Vec::new();
+        let mut feed_hashes = Vec::new();
+        let mut feed_min_responses = Vec::new();
         let mut queue = Pubkey::default();
 
         for feed in &params.feeds {
-            let data = context
-                .pull_feed_cache
-                .entry(*feed)
-                .or_insert_with(OnceCell::new)
-                .get_or_try_init(|| PullFeed::load_data(client, &feed))
-                .await?
-                .clone();
-            let num_sig_lower_bound = data.min_sample_size as u32 + ((data.min_sample_size as f64) / 3.0).ceil() as u32;
-            if num_signatures < num_sig_lower_bound {
-                num_signatures = num_sig_lower_bound;
-            }
+            let data = fetch_feed(&context, client, feed).await?;
+            feed_num_signatures.push((*feed, params.num_signatures_for(feed, &data)));
             queue = data.queue;
-            let jobs = context
-                .job_cache
-                .entry(data.feed_hash)
-                .or_insert_with(OnceCell::new)
-                .get_or_try_init(|| {
-                    let crossbar = params.crossbar.clone().unwrap_or_default();
-                    async move {
-                        let jobs_data = crossbar
-                            .fetch(&hex::encode(data.feed_hash))
-                            .await
-                            .context("PullFeed.fetchUpdateIx: Failed to fetch jobs")?;
-
-                        let jobs: Vec<OracleJob> = serde_json::from_value(jobs_data.get("jobs").unwrap().clone())
-                            .context("PullFeed.fetchUpdateIx: Failed to deserialize jobs")?;
-
-                        Ok::<Vec<OracleJob>, AnyhowError>(jobs)
-                    }
-                })
-            .await?.clone();
+            feed_hashes.push(data.feed_hash());
+            feed_min_responses.push(data.min_responses.max(1) as usize);
+            let jobs = fetch_jobs(context.clone(), &crossbar, data.feed_hash).await?;
             let encoded_jobs = encode_jobs(jobs);
+            if encoded_jobs.is_empty() {
+                return Err(FeedError::NoJobs {
+                    feed_hash: data.feed_hash(),
+                }
+                .into());
+            }
             let max_variance = (data.max_variance / 1_000_000_000) as u32;
             let min_responses = data.min_responses;
             let feed_config = FeedConfig {
@@ -376,65 +2027,125 @@ impl PullFeed {
             };
             feed_configs.push(feed_config);
         }
-        let latest_slot = SlotHashSysvar::get_latest_slothash(&client)
+        // A single request signs every feed with the same oracles
+        let num_signatures = feed_num_signatures
+            .iter()
+            .map(|(_, n)| *n)
+            .max()
+            .unwrap_or(1);
+        for (feed, n) in &feed_num_signatures {
+            let overridden = params
+                .num_signatures_overrides
+                .iter()
+                .any(|(k, _)| k == feed);
+            if overridden && *n < num_signatures {
+                tracing::warn!(
+                    feed = %feed,
+                    requested = n,
+                    num_signatures,
+                    "PullFeed.fetchUpdateManyIx: raising an overridden signature count to match the other feeds, see fetch_update_many_ixs"
+                );
+            }
+        }
+        let latest_slot = SlotHashSysvar::get_latest_slothash_cached(&context, client)
             .await
             .context("PullFeed.fetchUpdateIx: Failed to fetch latest slot")?;
+        let started = Instant::now();
         let price_signatures = gateway
             .fetch_signatures_multi(FetchSignaturesMultiParams {
-                recent_hash: Some(bs58::encode(latest_slot.hash.clone()).into_string()),
+                recent_hash: Some(latest_slot.hash_base58()),
                 num_signatures: Some(num_signatures),
                 feed_configs,
-                use_timestamp: Some(false),
+                use_timestamp: Some(params.use_timestamp),
             })
             .await
             .context("PullFeed.fetchUpdateIx: fetch signatures failure")?;
-        if params.debug.unwrap_or(false) {
-            println!("priceSignatures: {:?}", price_signatures);
-        }
+        tracing::debug!(
+            num_feeds = params.feeds.len(),
+            num_oracles = price_signatures.oracle_responses.len(),
+            latency_ms = started.elapsed().as_millis() as u64,
+            "PullFeed.fetchUpdateManyIx: fetched signatures"
+        );
+        tracing::trace!("PullFeed.fetchUpdateManyIx: {:?}", price_signatures);
 
+        // Each oracle answers every feed it could evaluate. Its values are laid
+        // out in `params.feeds` order, one slot per feed, with `i128::MAX` for
+        // the feeds it has no value for.
         let mut submissions: Vec<MultiSubmission> = Vec::new();
+        let mut oracle_keys: Vec<Pubkey> = Vec::new();
+        let mut oracle_timestamps = Vec::new();
         for x in &price_signatures.oracle_responses {
+            let oracle = x.feed_responses.iter().find_map(|r| {
+                let bytes: [u8; 32] = hex::decode(&r.oracle_pubkey).ok()?.try_into().ok()?;
+                Some(Pubkey::new_from_array(bytes))
+            });
+            let oracle = match oracle {
+                Some(oracle) => oracle,
+                None => {
+                    tracing::warn!("PullFeed.fetchUpdateManyIx: skipping an oracle response without an oracle key");
+                    continue;
+                }
+            };
+            let mut values = vec![i128::MAX; params.feeds.len()];
+            for (pos, resp) in x.feed_responses.iter().enumerate() {
+                let value = resp.success_value.parse().unwrap_or(i128::MAX);
+                let hash = resp.feed_hash.trim_start_matches("0x").to_lowercase();
+                let mut matched = false;
+                for (idx, feed_hash) in feed_hashes.iter().enumerate() {
+                    if *feed_hash == hash {
+                        values[idx] = value;
+                        matched = true;
+                    }
+                }
+                // Responses without a recognizable feed hash follow request order
+                if !matched && pos < values.len() {
+                    values[pos] = value;
+                }
+            }
             submissions.push(MultiSubmission {
-                values: x
-                    .feed_responses
-                    .iter()
-                    .map(|x| x.success_value.parse().unwrap_or(i128::MAX))
-                    .collect(),
-                    signature: base64
-                        .decode(x.signature.clone())
-                        .context("base64:decode failure")?
-                        .try_into()
-                        .map_err(|_| anyhow!("base64:decode failure"))?,
-                        recovery_id: x.recovery_id as u8,
+                values,
+                signature: base64
+                    .decode(x.signature.clone())
+                    .context("base64:decode failure")?
+                    .try_into()
+                    .map_err(|_| anyhow!("base64:decode failure"))?,
+                recovery_id: x.recovery_id as u8,
             });
+            oracle_keys.push(oracle);
+            oracle_timestamps.push((oracle, x.feed_responses.iter().find_map(|r| r.timestamp)));
         }
+        let feed_statuses = params
+            .feeds
+            .iter()
+            .enumerate()
+            .map(|(idx, feed)| {
+                let num_values = submissions.iter().filter(|x| !x.is_missing(idx)).count();
+                (*feed, num_values >= feed_min_responses[idx])
+            })
+            .collect();
         let ix_data = PullFeedSubmitResponseManyParams {
             slot: latest_slot.slot,
             submissions,
         };
         let mut remaining_accounts = Vec::new();
-        let oracle_keys: Vec<Pubkey> = price_signatures
-            .oracle_responses
-            .iter()
-            .map(|x| {
-                Pubkey::new_from_array(
-                    hex::decode(x.feed_responses.get(0).unwrap().oracle_pubkey.clone())
-                    .unwrap_or_default()
-                    .try_into()
-                    .unwrap(),
-                )
-            })
-        .collect();
         for feed in &params.feeds {
             remaining_accounts.push(AccountMeta::new(*feed, false));
         }
         for oracle in oracle_keys.iter() {
             remaining_accounts.push(AccountMeta::new_readonly(*oracle, false));
-            let stats_key = OracleAccountData::stats_key(&oracle);
+            let stats_key = OracleAccountData::stats_key_with_program_id(oracle, &context.program_id);
             remaining_accounts.push(AccountMeta::new(stats_key, false));
         }
 
 
+        let (reward_mint, token_program) = fetch_reward_mint(
+            &context,
+            client,
+            &queue,
+            params.reward_mint,
+            params.token_program,
+        )
+        .await?;
         let queue_key = [queue];
         let (oracle_luts_result, pull_feed_luts_result, queue_lut_result) = join!(
             fetch_and_cache_luts::<OracleAccountData>(client, context.clone(), &oracle_keys),
@@ -450,24 +2161,542 @@ impl PullFeed {
         let mut luts = oracle_luts;
         luts.extend(pull_feed_luts);
         luts.extend(queue_lut);
+        let luts = dedup_luts(luts);
 
         let mut submit_ix = Instruction {
-            program_id: *SWITCHBOARD_ON_DEMAND_PROGRAM_ID,
+            program_id: context.program_id,
             data: ix_data.data(),
             accounts: PullFeedSubmitResponseMany {
                 queue: queue,
-                program_state: State::key(),
+                program_state: State::key_with_program_id(&context.program_id),
                 recent_slothashes: solana_sdk::sysvar::slot_hashes::ID,
                 payer: params.payer,
                 system_program: system_program::ID,
-                reward_vault: get_associated_token_address(&queue, &NATIVE_MINT),
-                token_program: *SPL_TOKEN_PROGRAM_ID,
-                token_mint: *NATIVE_MINT,
+                reward_vault: get_associated_token_address_with_program_id(
+                    &queue,
+                    &reward_mint,
+                    &token_program,
+                ),
+                token_program,
+                token_mint: reward_mint,
             }
             .to_account_metas(None),
         };
         submit_ix.accounts.extend(remaining_accounts);
 
-        Ok((submit_ix, luts))
+        Ok(FetchUpdateManyReport {
+            ix: submit_ix,
+            luts,
+            feed_statuses,
+            oracle_timestamps,
+        })
+    }
+
+    /// Like `fetch_update_many_ix`, but partitions `params.feeds` into groups
+    /// that each fit in a single transaction (see
+    /// `estimate_update_many_tx_size`) and fetches an update for every group.
+    /// Feeds needing different numbers of signatures, e.g. through
+    /// `num_signatures_overrides`, are placed in separate groups.
+    /// # Arguments
+    /// * `client` - The RPC client
+    /// * `params` - The parameters for the fetch
+    pub async fn fetch_update_many_ixs(
+        context: Arc<SbContext>,
+        client: &impl AccountFetcher,
+        params: FetchUpdateManyParams,
+    ) -> Result<Vec<(Instruction, Vec<AddressLookupTableAccount>)>, AnyhowError> {
+        let mut feed_num_signatures = Vec::new();
+        for feed in &params.feeds {
+            let data = fetch_feed(&context, client, feed).await?;
+            feed_num_signatures.push((*feed, params.num_signatures_for(feed, &data)));
+        }
+        let chunks = Self::group_by_num_signatures(&feed_num_signatures)?;
+
+        let updates = chunks.into_iter().map(|(num_signatures, feeds)| {
+            let params = FetchUpdateManyParams {
+                feeds,
+                num_signatures: Some(num_signatures),
+                ..params.clone()
+            };
+            PullFeed::fetch_update_many_ix(context.clone(), client, params)
+        });
+        try_join_all(updates).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account_fetcher::MockFetcher;
+    use bytemuck::Zeroable;
+    use futures::future::join_all;
+    use std::sync::atomic::AtomicUsize;
+
+    fn account_bytes<T: bytemuck::Pod>(name: &str, data: &T) -> Vec<u8> {
+        let mut bytes = get_account_discriminator(name);
+        bytes.extend_from_slice(bytemuck::bytes_of(data));
+        bytes
+    }
+
+    fn feed_data(queue: Pubkey, min_sample_size: u8) -> PullFeedAccountData {
+        let mut data = PullFeedAccountData::zeroed();
+        data.queue = queue;
+        data.min_sample_size = min_sample_size;
+        data
+    }
+
+    fn queue_data(num_oracles: u32) -> QueueAccountData {
+        let mut data = QueueAccountData::zeroed();
+        data.oracle_keys_len = num_oracles;
+        data
+    }
+
+    fn slothash_bytes(slot: u64) -> Vec<u8> {
+        let mut bytes = 1u64.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&slot.to_le_bytes());
+        bytes.extend_from_slice(&[1; 32]);
+        bytes
+    }
+
+    /// A fetcher holding `feed`, its `queue` with `num_oracles` oracles and
+    /// the slothash sysvar at slot 100
+    fn update_fetcher(feed: Pubkey, queue: Pubkey, num_oracles: u32) -> MockFetcher {
+        let mut fetcher = MockFetcher::default();
+        fetcher.accounts.insert(
+            feed,
+            account_bytes("PullFeedAccountData", &feed_data(queue, 1)),
+        );
+        fetcher.accounts.insert(
+            queue,
+            account_bytes("QueueAccountData", &queue_data(num_oracles)),
+        );
+        fetcher
+            .accounts
+            .insert(solana_sdk::sysvar::slot_hashes::ID, slothash_bytes(100));
+        fetcher
+    }
+
+    fn requests(fetcher: &MockFetcher) -> Vec<Vec<Pubkey>> {
+        fetcher.requests.lock().unwrap().clone()
+    }
+
+    #[tokio::test]
+    async fn load_update_accounts_batches_a_known_queue() {
+        let (feed, queue) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let fetcher = update_fetcher(feed, queue, 4);
+        let accounts = PullFeed::load_update_accounts(&fetcher, &feed, Some(queue))
+            .await
+            .unwrap();
+        assert_eq!(accounts.feed.queue, queue);
+        assert_eq!(accounts.queue.oracle_keys_len, 4);
+        assert_eq!(accounts.slothash.slot, 100);
+        assert_eq!(
+            requests(&fetcher),
+            vec![vec![feed, solana_sdk::sysvar::slot_hashes::ID, queue]]
+        );
+    }
+
+    #[tokio::test]
+    async fn load_update_accounts_resolves_an_unknown_queue() {
+        let (feed, queue) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let fetcher = update_fetcher(feed, queue, 4);
+        let accounts = PullFeed::load_update_accounts(&fetcher, &feed, None)
+            .await
+            .unwrap();
+        assert_eq!(accounts.queue.oracle_keys_len, 4);
+        assert_eq!(
+            requests(&fetcher),
+            vec![vec![feed, solana_sdk::sysvar::slot_hashes::ID]]
+        );
+    }
+
+    #[tokio::test]
+    async fn load_update_accounts_rejects_another_queue() {
+        let (feed, queue) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let other = Pubkey::new_unique();
+        let mut fetcher = update_fetcher(feed, queue, 4);
+        fetcher
+            .accounts
+            .insert(other, account_bytes("QueueAccountData", &queue_data(9)));
+        let result = PullFeed::load_update_accounts(&fetcher, &feed, Some(other)).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn load_update_accounts_cached_fetches_only_missing_accounts() {
+        let (feed, queue) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let fetcher = update_fetcher(feed, queue, 4);
+        let context = SbContext::new();
+        let slot_hashes = solana_sdk::sysvar::slot_hashes::ID;
+
+        // Cold: the feed and slothash together, then the queue once known
+        let accounts = PullFeed::load_update_accounts_cached(&context, &fetcher, &feed)
+            .await
+            .unwrap();
+        assert_eq!(accounts.queue.oracle_keys_len, 4);
+        assert_eq!(requests(&fetcher), vec![vec![feed, slot_hashes]]);
+
+        // Warm: nothing is fetched
+        PullFeed::load_update_accounts_cached(&context, &fetcher, &feed)
+            .await
+            .unwrap();
+        assert_eq!(requests(&fetcher).len(), 1);
+
+        // Only the expired slothash is re-fetched
+        *context.slothash_cache.lock().unwrap() = None;
+        PullFeed::load_update_accounts_cached(&context, &fetcher, &feed)
+            .await
+            .unwrap();
+        assert_eq!(requests(&fetcher)[1], vec![slot_hashes]);
+
+        // Only the evicted queue is re-fetched
+        context.invalidate_queue(&queue);
+        let accounts = PullFeed::load_update_accounts_cached(&context, &fetcher, &feed)
+            .await
+            .unwrap();
+        assert_eq!(requests(&fetcher)[2], vec![queue]);
+        assert_eq!(accounts.feed.queue, queue);
+        assert_eq!(accounts.slothash.slot, 100);
+    }
+
+    #[tokio::test]
+    async fn concurrent_job_loads_share_one_fetch() {
+        let context = SbContext::new();
+        let feed_hash = [7u8; 32];
+        let fetches = AtomicUsize::new(0);
+        let loads = (0..8).map(|_| {
+            load_jobs(&context, feed_hash, || async {
+                fetches.fetch_add(1, Ordering::SeqCst);
+                // Keep the fetch in flight while the other loads start
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                Ok::<_, AnyhowError>(vec![OracleJob::default()])
+            })
+        });
+        let results = join_all(loads).await;
+
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        for result in results {
+            assert_eq!(result.unwrap(), vec![OracleJob::default()]);
+        }
+        // Later loads are served from the cache
+        let cached = load_jobs(&context, feed_hash, || async {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            Ok::<_, AnyhowError>(vec![])
+        })
+        .await
+        .unwrap();
+        assert_eq!(cached.len(), 1);
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn submission_value_overflow_returns_the_sentinel() {
+        assert_eq!(to_submission_value(Decimal::MAX), i128::MAX);
+        assert_eq!(to_submission_value(Decimal::MIN), i128::MAX);
+        // The smallest whole number that no longer fits 18 decimal places
+        let over = Decimal::from_i128_with_scale(79_228_162_515, 0);
+        assert_eq!(to_submission_value(over), i128::MAX);
+    }
+
+    #[test]
+    fn submission_value_keeps_the_largest_value_that_fits() {
+        let max_mantissa = 79_228_162_514_264_337_593_543_950_335i128;
+        let largest = Decimal::from_i128_with_scale(max_mantissa, PRECISION);
+        assert_eq!(to_submission_value(largest), max_mantissa);
+        assert_eq!(to_submission_value(-largest), -max_mantissa);
+        assert_eq!(
+            to_submission_value(Decimal::new(15, 1)),
+            1_500_000_000_000_000_000
+        );
+    }
+
+    #[test]
+    fn dedup_luts_keeps_the_first_of_each_key_in_order() {
+        let keys: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let lut = |key: Pubkey, addresses: usize| AddressLookupTableAccount {
+            key,
+            addresses: vec![Pubkey::default(); addresses],
+        };
+        let luts = vec![
+            lut(keys[1], 1),
+            lut(keys[0], 1),
+            lut(keys[1], 2),
+            lut(keys[2], 1),
+            lut(keys[0], 2),
+        ];
+        let deduped = dedup_luts(luts);
+        let deduped_keys: Vec<Pubkey> = deduped.iter().map(|x| x.key).collect();
+        assert_eq!(deduped_keys, vec![keys[1], keys[0], keys[2]]);
+        assert!(deduped.iter().all(|x| x.addresses.len() == 1));
+        assert!(dedup_luts(Vec::new()).is_empty());
+    }
+
+    /// The serialized size of a compiled update for `num_feeds` feeds signed
+    /// by `num_oracles` oracles, laid out as `fetch_update_many_ix` does
+    fn compiled_update_many_tx_size(num_feeds: usize, num_oracles: usize) -> usize {
+        use solana_sdk::signer::Signer;
+
+        let payer = Keypair::new();
+        let program_id = Pubkey::new_unique();
+        let queue = Pubkey::new_unique();
+        let token_program = *SPL_TOKEN_PROGRAM_ID;
+        let mut accounts = PullFeedSubmitResponseMany {
+            queue,
+            program_state: State::key_with_program_id(&program_id),
+            recent_slothashes: solana_sdk::sysvar::slot_hashes::ID,
+            payer: payer.pubkey(),
+            system_program: system_program::ID,
+            reward_vault: get_associated_token_address_with_program_id(
+                &queue,
+                &NATIVE_MINT,
+                &token_program,
+            ),
+            token_program,
+            token_mint: *NATIVE_MINT,
+        }
+        .to_account_metas(None);
+        let mut luts = vec![AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: accounts
+                .iter()
+                .map(|x| x.pubkey)
+                .filter(|x| *x != payer.pubkey())
+                .collect(),
+        }];
+        for _ in 0..num_feeds {
+            let feed = Pubkey::new_unique();
+            accounts.push(AccountMeta::new(feed, false));
+            luts.push(AddressLookupTableAccount {
+                key: Pubkey::new_unique(),
+                addresses: vec![feed],
+            });
+        }
+        for _ in 0..num_oracles {
+            let oracle = Pubkey::new_unique();
+            let stats = OracleAccountData::stats_key_with_program_id(&oracle, &program_id);
+            accounts.push(AccountMeta::new_readonly(oracle, false));
+            accounts.push(AccountMeta::new(stats, false));
+            luts.push(AddressLookupTableAccount {
+                key: Pubkey::new_unique(),
+                addresses: vec![oracle, stats],
+            });
+        }
+        let submission = MultiSubmission {
+            values: vec![0; num_feeds],
+            signature: [0; 64],
+            recovery_id: 0,
+        };
+        let submit_ix = Instruction {
+            program_id,
+            data: PullFeedSubmitResponseManyParams {
+                slot: 1,
+                submissions: vec![submission; num_oracles],
+            }
+            .data(),
+            accounts,
+        };
+        let ixs = [
+            ComputeBudgetInstruction::set_compute_unit_limit(1_400_000),
+            ComputeBudgetInstruction::set_compute_unit_price(1),
+            submit_ix,
+        ];
+        let tx =
+            ix_to_versioned_tx(&ixs, &[&payer], &luts, solana_sdk::hash::Hash::default()).unwrap();
+        bincode::serialize(&tx).unwrap().len()
+    }
+
+    #[test]
+    fn update_many_tx_size_estimate_matches_compiled_transactions() {
+        for num_oracles in [1, 3, 5, 10] {
+            let max_feeds = PullFeed::max_feeds_per_update(num_oracles);
+            for num_feeds in 1..=max_feeds + 1 {
+                assert_eq!(
+                    compiled_update_many_tx_size(num_feeds, num_oracles),
+                    PullFeed::estimate_update_many_tx_size(num_feeds, num_oracles),
+                    "{} feeds, {} oracles",
+                    num_feeds,
+                    num_oracles
+                );
+            }
+            if max_feeds > 0 {
+                assert!(compiled_update_many_tx_size(max_feeds, num_oracles) <= PACKET_DATA_SIZE);
+            }
+            assert!(compiled_update_many_tx_size(max_feeds + 1, num_oracles) > PACKET_DATA_SIZE);
+        }
+        // Ten signatures leave no room for even a single feed
+        assert_eq!(PullFeed::max_feeds_per_update(10), 0);
+    }
+
+    #[test]
+    fn num_signatures_for_clamps_overrides_and_defaults() {
+        let (feed, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let data = feed_data(Pubkey::new_unique(), 5);
+        let mut params = FetchUpdateManyParams {
+            num_signatures_overrides: vec![(feed, 2)],
+            ..Default::default()
+        };
+        // An override below min_sample_size is raised to it
+        assert_eq!(params.num_signatures_for(&feed, &data), 5);
+        // Without an override, the min_sample_size based lower bound applies
+        assert_eq!(params.num_signatures_for(&other, &data), 7);
+        params.num_signatures = Some(10);
+        assert_eq!(params.num_signatures_for(&other, &data), 10);
+        // An override replaces that lower bound
+        params.num_signatures_overrides = vec![(feed, 6)];
+        assert_eq!(params.num_signatures_for(&feed, &data), 6);
+    }
+
+    #[test]
+    fn group_by_num_signatures_groups_then_chunks() {
+        let keys: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let groups =
+            PullFeed::group_by_num_signatures(&[(keys[0], 1), (keys[1], 2), (keys[2], 1)]).unwrap();
+        assert_eq!(
+            groups,
+            vec![(1, vec![keys[0], keys[2]]), (2, vec![keys[1]])]
+        );
+
+        let max_feeds = PullFeed::max_feeds_per_update(3);
+        let feeds: Vec<(Pubkey, u32)> = (0..max_feeds + 1)
+            .map(|_| (Pubkey::new_unique(), 3))
+            .collect();
+        let groups = PullFeed::group_by_num_signatures(&feeds).unwrap();
+        let sizes: Vec<(u32, usize)> = groups.iter().map(|(n, x)| (*n, x.len())).collect();
+        assert_eq!(sizes, vec![(3, max_feeds), (3, 1)]);
+
+        assert!(PullFeed::group_by_num_signatures(&[(keys[0], 10)]).is_err());
+        assert!(PullFeed::group_by_num_signatures(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn capped_num_signatures_caps_to_the_queue() {
+        let feed = Pubkey::new_unique();
+        let mut data = feed_data(Pubkey::new_unique(), 3);
+        data.min_responses = 2;
+        // The default is min_sample_size plus a third
+        assert_eq!(capped_num_signatures(&feed, None, &data, 10).unwrap(), 4);
+        // Requests the queue can serve are kept, even below the default
+        assert_eq!(capped_num_signatures(&feed, Some(2), &data, 10).unwrap(), 2);
+        assert_eq!(
+            capped_num_signatures(&feed, Some(10), &data, 10).unwrap(),
+            10
+        );
+        // Larger ones are capped, with a warning
+        assert_eq!(capped_num_signatures(&feed, Some(8), &data, 5).unwrap(), 5);
+        assert_eq!(capped_num_signatures(&feed, None, &data, 3).unwrap(), 3);
+    }
+
+    #[test]
+    fn capped_num_signatures_rejects_too_few_oracles() {
+        let feed = Pubkey::new_unique();
+        let mut data = feed_data(Pubkey::new_unique(), 3);
+        assert!(capped_num_signatures(&feed, None, &data, 0).is_err());
+        assert!(capped_num_signatures(&feed, Some(1), &data, 0).is_err());
+        // Capping below min_sample_size
+        assert!(capped_num_signatures(&feed, Some(8), &data, 2).is_err());
+        // Capping below min_responses
+        data.min_sample_size = 1;
+        data.min_responses = 4;
+        assert!(capped_num_signatures(&feed, Some(8), &data, 3).is_err());
+        assert_eq!(capped_num_signatures(&feed, Some(8), &data, 4).unwrap(), 4);
+    }
+
+    /// Answers a single gateway `fetch_signatures` request with no oracle
+    /// responses. Resolves to the `num_oracles` that was requested.
+    async fn stub_gateway() -> (Gateway, tokio::task::JoinHandle<u64>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let gateway = Gateway::new(format!("http://{}", listener.local_addr().unwrap()));
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 4096];
+            let body = loop {
+                let n = socket.read(&mut buf).await.unwrap();
+                assert!(n > 0, "connection closed mid-request");
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some(end) = text.find("\r\n\r\n") {
+                    let content_length = text[..end]
+                        .lines()
+                        .filter_map(|line| line.split_once(':'))
+                        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+                        .map(|(_, value)| value.trim().parse::<usize>().unwrap())
+                        .unwrap_or(0);
+                    if text.len() >= end + 4 + content_length {
+                        break text[end + 4..].to_string();
+                    }
+                }
+            };
+            let response = r#"{"responses":[],"caller":"","failures":[]}"#;
+            socket
+                .write_all(
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        response.len(),
+                        response
+                    )
+                    .as_bytes(),
+                )
+                .await
+                .unwrap();
+            let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+            body["num_oracles"].as_u64().unwrap()
+        });
+        (gateway, handle)
+    }
+
+    fn update_params(feed: Pubkey, gateway: Gateway, num_signatures: u32) -> FetchUpdateParams {
+        FetchUpdateParams {
+            feed,
+            payer: Pubkey::new_unique(),
+            gateway,
+            num_signatures: Some(num_signatures),
+            jobs: Some(vec![OracleJob::default()]),
+            reward_mint: Some(*NATIVE_MINT),
+            token_program: Some(*SPL_TOKEN_PROGRAM_ID),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn fetch_update_report_requests_the_capped_num_signatures() {
+        let (feed, queue) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let fetcher = update_fetcher(feed, queue, 5);
+        let context = SbContext::new();
+        for key in [feed, queue] {
+            context.lut_cache.insert(
+                key,
+                AddressLookupTableAccount {
+                    key: Pubkey::new_unique(),
+                    addresses: Vec::new(),
+                },
+            );
+        }
+        let (gateway, requested) = stub_gateway().await;
+
+        let report =
+            PullFeed::fetch_update_report(context, &fetcher, update_params(feed, gateway, 8))
+                .await
+                .unwrap();
+        assert_eq!(report.num_signatures, 5);
+        assert_eq!(requested.await.unwrap(), 5);
+        assert!(report.responses.is_empty());
+    }
+
+    #[tokio::test]
+    async fn fetch_update_report_rejects_a_queue_without_oracles() {
+        let (feed, queue) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let fetcher = update_fetcher(feed, queue, 0);
+        // Nothing listens here: the fetch must fail before reaching the gateway
+        let gateway = Gateway::new("http://127.0.0.1:1".to_string());
+        let result = PullFeed::fetch_update_report(
+            SbContext::new(),
+            &fetcher,
+            update_params(feed, gateway, 8),
+        )
+        .await;
+        assert!(format!("{:#}", result.unwrap_err()).contains("has no oracles"));
     }
 }
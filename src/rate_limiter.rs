@@ -0,0 +1,48 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A token bucket limiting how often a client calls a shared service, e.g. a
+/// `Gateway`. Tokens refill continuously at `requests_per_second` up to `burst`.
+#[derive(Debug)]
+pub struct RateLimiter {
+    requests_per_second: f64,
+    burst: f64,
+    // (available tokens, time of the last refill)
+    state: Mutex<(f64, Instant)>,
+}
+
+impl RateLimiter {
+    /// Creates a full bucket. `requests_per_second` and `burst` are floored
+    /// at a small positive rate and a single request respectively.
+    pub fn new(requests_per_second: f64, burst: u32) -> Self {
+        let burst = burst.max(1) as f64;
+        Self {
+            requests_per_second: requests_per_second.max(0.001),
+            burst,
+            state: Mutex::new((burst, Instant::now())),
+        }
+    }
+
+    /// Takes a token if one is available, otherwise returns how long until one is
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        let elapsed = now.duration_since(state.1).as_secs_f64();
+        state.0 = (state.0 + elapsed * self.requests_per_second).min(self.burst);
+        state.1 = now;
+        if state.0 >= 1.0 {
+            state.0 -= 1.0;
+            return Ok(());
+        }
+        Err(Duration::from_secs_f64(
+            (1.0 - state.0) / self.requests_per_second,
+        ))
+    }
+
+    /// Waits until a token is available and takes it
+    pub async fn acquire(&self) {
+        while let Err(wait) = self.try_acquire() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
@@ -1,10 +1,14 @@
+use crate::AccountFetcher;
+use crate::SbContext;
+use anyhow_ext::anyhow;
 use anyhow_ext::Context;
 use anyhow_ext::Error as AnyhowError;
 use arrayref::array_ref;
 use bytemuck;
-use solana_client::nonblocking::rpc_client::RpcClient;
 use std::result::Result;
 use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::slot_hashes::MAX_ENTRIES;
+use std::time::{Duration, Instant};
 
 #[repr(C)]
 #[derive(bytemuck::Pod, bytemuck::Zeroable, Debug, Clone, Copy)]
@@ -13,20 +17,208 @@ pub struct SlotHash {
     pub hash: [u8; 32],
 }
 
+impl SlotHash {
+    /// The hash encoded as base58, the encoding gateways expect for `recent_hash`
+    pub fn hash_base58(&self) -> String {
+        bs58::encode(self.hash).into_string()
+    }
+
+    /// The hash encoded as hex
+    pub fn hash_hex(&self) -> String {
+        hex::encode(self.hash)
+    }
+}
+
+/// Offset of the first entry in the slot_hashes sysvar, after its u64 length prefix
+const SLOT_HASHES_OFFSET: usize = 8;
+/// Size of a serialized (slot, hash) entry
+const SLOT_HASH_SIZE: usize = std::mem::size_of::<SlotHash>();
+
+/// How long a cached slothash is reused: roughly one slot.
+pub const SLOTHASH_CACHE_TTL: Duration = Duration::from_millis(400);
+
 pub struct SlotHashSysvar;
 impl<'a> SlotHashSysvar {
-    pub async fn get_latest_slothash(client: &RpcClient) -> Result<SlotHash, AnyhowError> {
-        let slots_data = client.get_account_with_commitment(
+    pub async fn get_latest_slothash(
+        client: &impl AccountFetcher,
+    ) -> Result<SlotHash, AnyhowError> {
+        Self::get_latest_slothash_with_commitment(client, CommitmentConfig::confirmed()).await
+    }
+
+    /// Returns the latest slothash, reusing the context's cached value if it
+    /// was fetched within `SLOTHASH_CACHE_TTL` (i.e. the slot has not advanced yet).
+    pub async fn get_latest_slothash_cached(
+        context: &SbContext,
+        client: &impl AccountFetcher,
+    ) -> Result<SlotHash, AnyhowError> {
+        let cached = *context.slothash_cache.lock().unwrap();
+        if let Some((fetched_at, slothash)) = cached {
+            if fetched_at.elapsed() < SLOTHASH_CACHE_TTL {
+                return Ok(slothash);
+            }
+        }
+        let slothash = Self::get_latest_slothash(client).await?;
+        *context.slothash_cache.lock().unwrap() = Some((Instant::now(), slothash));
+        Ok(slothash)
+    }
+
+    pub async fn get_latest_slothash_with_commitment(
+        client: &impl AccountFetcher,
+        commitment: CommitmentConfig,
+    ) -> Result<SlotHash, AnyhowError> {
+        let slots_data = client
+            .get_account_with_commitment(&solana_sdk::sysvar::slot_hashes::ID, commitment)
+            .await
+            .context("Failed to fetch slot hashes")?
+            .context("Failed to fetch slot hashes")?
+            .data;
+        Self::parse_latest(&slots_data)
+    }
+
+    /// Returns the `n` most recent slothashes, most recent first. Useful for
+    /// falling back to a slightly older hash that oracles already recognize.
+    pub async fn get_recent_slothashes(
+        client: &impl AccountFetcher,
+        n: usize,
+    ) -> Result<Vec<SlotHash>, AnyhowError> {
+        let slots_data = client
+            .get_account_with_commitment(
                 &solana_sdk::sysvar::slot_hashes::ID,
-                CommitmentConfig::confirmed())
+                CommitmentConfig::confirmed(),
+            )
             .await
             .context("Failed to fetch slot hashes")?
-            .value
             .context("Failed to fetch slot hashes")?
             .data;
-        let slots: &[u8] = array_ref![slots_data, 8, 20_480];
-        // 20_480 / 40 = 512
-        let slots: &[SlotHash] = bytemuck::cast_slice::<u8, SlotHash>(slots);
-        Ok(slots[0])
+        let mut slots = Self::parse_all(&slots_data)?;
+        slots.truncate(n);
+        Ok(slots)
+    }
+
+    /// Parses the most recent entry from raw slot_hashes sysvar account data
+    pub fn parse_latest(data: &[u8]) -> Result<SlotHash, AnyhowError> {
+        if Self::entries_len(data)? == 0 {
+            return Err(anyhow!("Slot hashes sysvar is empty"));
+        }
+        let entry = data
+            .get(SLOT_HASHES_OFFSET..SLOT_HASHES_OFFSET + SLOT_HASH_SIZE)
+            .context("Slot hashes data truncated")?;
+        Ok(bytemuck::pod_read_unaligned::<SlotHash>(entry))
+    }
+
+    /// Parses every entry (up to 512, most recent first) from raw slot_hashes
+    /// sysvar account data
+    pub fn parse_all(data: &[u8]) -> Result<Vec<SlotHash>, AnyhowError> {
+        let len = Self::entries_len(data)?;
+        let entries = data
+            .get(SLOT_HASHES_OFFSET..SLOT_HASHES_OFFSET + len * SLOT_HASH_SIZE)
+            .context("Slot hashes data truncated")?;
+        Ok(entries
+            .chunks_exact(SLOT_HASH_SIZE)
+            .map(bytemuck::pod_read_unaligned::<SlotHash>)
+            .collect())
+    }
+
+    /// Reads the u64 length prefix of the sysvar, capped at `MAX_ENTRIES`
+    fn entries_len(data: &[u8]) -> Result<usize, AnyhowError> {
+        if data.len() < SLOT_HASHES_OFFSET {
+            return Err(anyhow!("Slot hashes data too short"));
+        }
+        let len = u64::from_le_bytes(*array_ref![data, 0, SLOT_HASHES_OFFSET]) as usize;
+        Ok(len.min(MAX_ENTRIES))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::hash::Hash;
+    use solana_sdk::slot_hashes::SlotHashes;
+
+    const LATEST_SLOT: u64 = 287_310_921;
+    const LATEST_HASH: [u8; 32] = [
+        0x3c, 0x9a, 0x0e, 0x51, 0x7d, 0x22, 0xb4, 0x6f, 0x81, 0x05, 0xe9, 0x13, 0xaa, 0x47,
+        0x6c, 0xd0, 0x2e, 0xf8, 0x59, 0x94, 0x1b, 0xc3, 0x70, 0x0a, 0x88, 0x36, 0xdf, 0x65,
+        0x12, 0xbe, 0x4d, 0xf1,
+    ];
+    const PREVIOUS_SLOT: u64 = 287_310_920;
+    const PREVIOUS_HASH: [u8; 32] = [0x11; 32];
+
+    /// A slot_hashes sysvar buffer holding two entries, most recent first,
+    /// zero padded to the sysvar's fixed account size like on chain
+    fn sysvar_fixture() -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&2u64.to_le_bytes());
+        data.extend_from_slice(&LATEST_SLOT.to_le_bytes());
+        data.extend_from_slice(&LATEST_HASH);
+        data.extend_from_slice(&PREVIOUS_SLOT.to_le_bytes());
+        data.extend_from_slice(&PREVIOUS_HASH);
+        data.resize(SLOT_HASHES_OFFSET + MAX_ENTRIES * SLOT_HASH_SIZE, 0);
+        data
+    }
+
+    #[test]
+    fn fixture_matches_the_sysvar_encoding() {
+        let slot_hashes = SlotHashes::new(&[
+            (PREVIOUS_SLOT, Hash::new_from_array(PREVIOUS_HASH)),
+            (LATEST_SLOT, Hash::new_from_array(LATEST_HASH)),
+        ]);
+        let encoded = bincode::serialize(&slot_hashes).unwrap();
+        assert_eq!(&sysvar_fixture()[..encoded.len()], encoded.as_slice());
+    }
+
+    #[test]
+    fn parse_latest_reads_the_most_recent_entry() {
+        let latest = SlotHashSysvar::parse_latest(&sysvar_fixture()).unwrap();
+        assert_eq!(latest.slot, LATEST_SLOT);
+        assert_eq!(latest.hash, LATEST_HASH);
+    }
+
+    #[test]
+    fn parse_all_reads_every_entry_in_order() {
+        let all = SlotHashSysvar::parse_all(&sysvar_fixture()).unwrap();
+        let entries: Vec<(u64, [u8; 32])> = all.iter().map(|x| (x.slot, x.hash)).collect();
+        assert_eq!(
+            entries,
+            vec![(LATEST_SLOT, LATEST_HASH), (PREVIOUS_SLOT, PREVIOUS_HASH)]
+        );
+    }
+
+    #[test]
+    fn parse_rejects_truncated_data() {
+        let data = sysvar_fixture();
+        assert!(SlotHashSysvar::parse_latest(&data[..4]).is_err());
+        assert!(SlotHashSysvar::parse_latest(&data[..SLOT_HASHES_OFFSET + 16]).is_err());
+        assert!(SlotHashSysvar::parse_all(&data[..SLOT_HASHES_OFFSET + SLOT_HASH_SIZE]).is_err());
+        let mut empty = data;
+        empty[..SLOT_HASHES_OFFSET].copy_from_slice(&0u64.to_le_bytes());
+        assert!(SlotHashSysvar::parse_latest(&empty).is_err());
+    }
+
+    #[tokio::test]
+    async fn fetches_through_an_account_fetcher() {
+        let mut fetcher = crate::account_fetcher::MockFetcher::default();
+        fetcher
+            .accounts
+            .insert(solana_sdk::sysvar::slot_hashes::ID, sysvar_fixture());
+        let latest = SlotHashSysvar::get_latest_slothash(&fetcher).await.unwrap();
+        assert_eq!(latest.slot, LATEST_SLOT);
+        let recent = SlotHashSysvar::get_recent_slothashes(&fetcher, 1)
+            .await
+            .unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].hash, LATEST_HASH);
+
+        // A fresh cached slothash is served without reading the sysvar again
+        let context = SbContext::new();
+        SlotHashSysvar::get_latest_slothash_cached(&context, &fetcher)
+            .await
+            .unwrap();
+        let empty = crate::account_fetcher::MockFetcher::default();
+        let cached = SlotHashSysvar::get_latest_slothash_cached(&context, &empty)
+            .await
+            .unwrap();
+        assert_eq!(cached.slot, LATEST_SLOT);
+        assert!(SlotHashSysvar::get_latest_slothash(&empty).await.is_err());
     }
 }
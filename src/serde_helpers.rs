@@ -0,0 +1,108 @@
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serializer};
+
+/// Serializes an `i128` as a decimal string, since JSON numbers lose precision
+/// beyond 53 bits.
+pub mod i128_string {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &i128, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i128, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+/// Serializes a byte array as a hex string.
+pub mod hex_bytes {
+    use super::*;
+
+    pub fn serialize<S: Serializer, const N: usize>(
+        value: &[u8; N],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(value))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<[u8; N], D::Error> {
+        let bytes = hex::decode(String::deserialize(deserializer)?).map_err(D::Error::custom)?;
+        bytes
+            .try_into()
+            .map_err(|_| D::Error::custom(format!("expected {} bytes", N)))
+    }
+}
+
+/// Serializes a byte array as a base64 string.
+pub mod base64_bytes {
+    use super::*;
+    use base64::{engine::general_purpose::STANDARD as base64, Engine as _};
+
+    pub fn serialize<S: Serializer, const N: usize>(
+        value: &[u8; N],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&base64.encode(value))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<[u8; N], D::Error> {
+        let bytes = base64
+            .decode(String::deserialize(deserializer)?)
+            .map_err(D::Error::custom)?;
+        bytes
+            .try_into()
+            .map_err(|_| D::Error::custom(format!("expected {} bytes", N)))
+    }
+}
+
+/// Serializes a `Pubkey` as a base58 string.
+pub mod pubkey_string {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+    use std::str::FromStr;
+
+    pub fn serialize<S: Serializer>(value: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+        Pubkey::from_str(&String::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
+/// Serializes a NUL padded byte array as a string, truncated at the first NUL.
+pub mod nul_padded_string {
+    use super::*;
+
+    pub fn serialize<S: Serializer, const N: usize>(
+        value: &[u8; N],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let end = value.iter().position(|x| *x == 0).unwrap_or(N);
+        serializer.serialize_str(&String::from_utf8_lossy(&value[..end]))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<[u8; N], D::Error> {
+        let value = String::deserialize(deserializer)?;
+        if value.len() > N {
+            return Err(D::Error::custom(format!("longer than {} bytes", N)));
+        }
+        let mut bytes = [0; N];
+        bytes[..value.len()].copy_from_slice(value.as_bytes());
+        Ok(bytes)
+    }
+}
+
+/// The default for skipped reserved and padding fields.
+pub fn zeroed<const N: usize>() -> [u8; N] {
+    [0; N]
+}